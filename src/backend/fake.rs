@@ -0,0 +1,565 @@
+use anyhow::{anyhow, Result};
+use serenity::all::{
+    ChannelId, ChannelType, GuildId, MessageId, PermissionOverwrite, Permissions, RoleId,
+    Timestamp, UserId,
+};
+use serenity::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use super::{
+    AttachmentData, Backend, ButtonData, ChannelData, ChannelEdit, EmbedData, FileData,
+    InteractionHandle, MemberData, MessageData, NewChannel, NotFound, RoleData, TooLarge,
+};
+
+/// User id the fake guild uses as author of the bot's messages
+pub const BOT_USER_ID: u64 = 1;
+
+#[derive(Default)]
+struct FakeState {
+    next_id: u64,
+    channels: BTreeMap<ChannelId, ChannelData>,
+    roles: BTreeMap<RoleId, RoleData>,
+    members: HashMap<UserId, Vec<RoleId>>,
+    /// Messages of each channel, oldest first
+    messages: HashMap<ChannelId, Vec<MessageData>>,
+    responses: Vec<String>,
+    /// Buttons of the latest reply that had any
+    buttons: Vec<ButtonData>,
+    /// Uploaded files with the channel they were posted in, `None` for interaction replies
+    files: Vec<(Option<ChannelId>, FileData)>,
+    write_calls: usize,
+    /// Channels the bot cannot read, as if it lost access to them
+    inaccessible: HashSet<ChannelId>,
+    /// Largest file the guild accepts, in bytes
+    upload_limit: Option<usize>,
+}
+
+impl FakeState {
+    fn check_upload(&self, files: &[FileData]) -> Result<()> {
+        match self.upload_limit {
+            Some(limit) if files.iter().any(|f| f.data.len() > limit) => {
+                Err(TooLarge("Request entity too large".to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn unknown(what: &str, id: impl Display) -> anyhow::Error {
+    NotFound(format!("Unknown {} {}", what, id)).into()
+}
+
+impl FakeState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn channel_mut(&mut self, channel_id: ChannelId) -> Result<&mut ChannelData> {
+        self.channels
+            .get_mut(&channel_id)
+            .ok_or_else(|| unknown("Channel", channel_id))
+    }
+
+    fn messages_mut(&mut self, channel_id: ChannelId) -> Result<&mut Vec<MessageData>> {
+        if !self.channels.contains_key(&channel_id) {
+            return Err(unknown("Channel", channel_id));
+        }
+        if self.inaccessible.contains(&channel_id) {
+            return Err(anyhow!("Missing Access"));
+        }
+        Ok(self.messages.entry(channel_id).or_default())
+    }
+}
+
+/// In-memory guild implementing [`Backend`], used to exercise commands without Discord
+pub struct FakeGuild {
+    guild_id: GuildId,
+    state: Mutex<FakeState>,
+}
+
+impl FakeGuild {
+    /// Empty guild with only the @everyone role, which shares the guild id
+    pub fn new(guild_id: u64) -> Self {
+        let everyone = RoleId::new(guild_id);
+        let roles = BTreeMap::from([(
+            everyone,
+            RoleData {
+                id: everyone,
+                name: "@everyone".to_string(),
+                permissions: Permissions::VIEW_CHANNEL
+                    | Permissions::SEND_MESSAGES
+                    | Permissions::READ_MESSAGE_HISTORY
+                    | Permissions::EMBED_LINKS,
+            },
+        )]);
+
+        Self {
+            guild_id: GuildId::new(guild_id),
+            state: Mutex::new(FakeState {
+                next_id: guild_id,
+                roles,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn guild_id(&self) -> GuildId {
+        self.guild_id
+    }
+
+    /// Add a channel directly, as if created by hand in the Discord client
+    pub fn add_channel(
+        &self,
+        name: &str,
+        kind: ChannelType,
+        parent_id: Option<ChannelId>,
+    ) -> ChannelId {
+        let mut state = self.state.lock().unwrap();
+        let id = ChannelId::new(state.next_id());
+        state.channels.insert(
+            id,
+            ChannelData {
+                id,
+                guild_id: self.guild_id,
+                name: name.to_string(),
+                kind,
+                parent_id,
+                topic: None,
+                permission_overwrites: Vec::new(),
+            },
+        );
+        id
+    }
+
+    /// Add or replace a role
+    pub fn add_role(&self, role_id: u64, name: &str, permissions: Permissions) -> RoleId {
+        let id = RoleId::new(role_id);
+        self.state.lock().unwrap().roles.insert(
+            id,
+            RoleData {
+                id,
+                name: name.to_string(),
+                permissions,
+            },
+        );
+        id
+    }
+
+    pub fn remove_role(&self, role_id: RoleId) {
+        self.state.lock().unwrap().roles.remove(&role_id);
+    }
+
+    /// Add a member with the given roles, replacing an existing one
+    pub fn add_member(&self, user_id: u64, roles: Vec<RoleId>) {
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .insert(UserId::new(user_id), roles);
+    }
+
+    /// Replace the overwrites of a channel, as a moderator editing them by hand would
+    pub fn set_permission_overwrites(
+        &self,
+        channel_id: ChannelId,
+        overwrites: Vec<PermissionOverwrite>,
+    ) {
+        if let Some(channel) = self.state.lock().unwrap().channels.get_mut(&channel_id) {
+            channel.permission_overwrites = overwrites;
+        }
+    }
+
+    pub fn channel_data(&self, channel_id: ChannelId) -> Option<ChannelData> {
+        self.state
+            .lock()
+            .unwrap()
+            .channels
+            .get(&channel_id)
+            .cloned()
+    }
+
+    pub fn channel_named(&self, name: &str) -> Option<ChannelData> {
+        self.state
+            .lock()
+            .unwrap()
+            .channels
+            .values()
+            .find(|c| c.name == name)
+            .cloned()
+    }
+
+    /// Messages of a channel, oldest first
+    pub fn channel_messages(&self, channel_id: ChannelId) -> Vec<MessageData> {
+        self.state
+            .lock()
+            .unwrap()
+            .messages
+            .get(&channel_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Every interaction reply and reply edit, in order
+    pub fn responses(&self) -> Vec<String> {
+        self.state.lock().unwrap().responses.clone()
+    }
+
+    /// Buttons of the latest reply that had any, cleared when a click updates it
+    pub fn buttons(&self) -> Vec<ButtonData> {
+        self.state.lock().unwrap().buttons.clone()
+    }
+
+    /// Add messages written by a member, as if posted in the Discord client
+    pub fn add_messages(&self, channel_id: ChannelId, author_id: u64, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        for _ in 0..count {
+            let id = MessageId::new(state.next_id());
+            if let Ok(messages) = state.messages_mut(channel_id) {
+                messages.push(MessageData {
+                    id,
+                    author_id: UserId::new(author_id),
+                    author_name: format!("user{}", author_id),
+                    timestamp: Timestamp::now(),
+                    content: "message".to_string(),
+                    embeds: Vec::new(),
+                    attachments: Vec::new(),
+                });
+            }
+        }
+    }
+
+    /// Add a message with an attachment, as if posted in the Discord client
+    pub fn add_attachment(&self, channel_id: ChannelId, author_id: u64, filename: &str) {
+        let mut state = self.state.lock().unwrap();
+        let id = MessageId::new(state.next_id());
+        if let Ok(messages) = state.messages_mut(channel_id) {
+            messages.push(MessageData {
+                id,
+                author_id: UserId::new(author_id),
+                author_name: format!("user{}", author_id),
+                timestamp: Timestamp::now(),
+                content: String::new(),
+                embeds: Vec::new(),
+                attachments: vec![AttachmentData {
+                    filename: filename.to_string(),
+                    url: format!("https://cdn.example/{}", filename),
+                    size: 1024,
+                    content_type: None,
+                }],
+            });
+        }
+    }
+
+    /// Files posted in a channel, oldest first
+    pub fn files(&self, channel_id: ChannelId) -> Vec<FileData> {
+        self.state
+            .lock()
+            .unwrap()
+            .files
+            .iter()
+            .filter(|(c, _)| *c == Some(channel_id))
+            .map(|(_, f)| f.clone())
+            .collect()
+    }
+
+    /// Files attached to interaction replies, oldest first
+    pub fn response_files(&self) -> Vec<FileData> {
+        self.state
+            .lock()
+            .unwrap()
+            .files
+            .iter()
+            .filter(|(c, _)| c.is_none())
+            .map(|(_, f)| f.clone())
+            .collect()
+    }
+
+    /// Make every read of a channel fail with Missing Access, or succeed again
+    pub fn set_accessible(&self, channel_id: ChannelId, accessible: bool) {
+        let mut state = self.state.lock().unwrap();
+        if accessible {
+            state.inaccessible.remove(&channel_id);
+        } else {
+            state.inaccessible.insert(channel_id);
+        }
+    }
+
+    /// Refuse uploaded files larger than `bytes`, like Discord's size limit
+    pub fn set_upload_limit(&self, bytes: usize) {
+        self.state.lock().unwrap().upload_limit = Some(bytes);
+    }
+
+    /// Number of calls that modified channels or messages
+    pub fn write_calls(&self) -> usize {
+        self.state.lock().unwrap().write_calls
+    }
+
+    pub fn reset_write_calls(&self) {
+        self.state.lock().unwrap().write_calls = 0;
+    }
+}
+
+#[async_trait]
+impl Backend for FakeGuild {
+    async fn channel(&self, channel_id: ChannelId) -> Result<ChannelData> {
+        if self
+            .state
+            .lock()
+            .unwrap()
+            .inaccessible
+            .contains(&channel_id)
+        {
+            return Err(anyhow!("Missing Access"));
+        }
+        self.channel_data(channel_id)
+            .ok_or_else(|| unknown("Channel", channel_id))
+    }
+
+    async fn guild_channels(&self, guild_id: GuildId) -> Result<Vec<ChannelData>> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Guild", guild_id));
+        }
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .channels
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn guild_roles(&self, guild_id: GuildId) -> Result<Vec<RoleData>> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Guild", guild_id));
+        }
+        Ok(self.state.lock().unwrap().roles.values().cloned().collect())
+    }
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<MemberData> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Guild", guild_id));
+        }
+        let state = self.state.lock().unwrap();
+        let roles = state
+            .members
+            .get(&user_id)
+            .ok_or_else(|| unknown("Member", user_id))?;
+        Ok(MemberData {
+            roles: roles.clone(),
+        })
+    }
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelData> {
+        if guild_id != self.guild_id {
+            return Err(unknown("Guild", guild_id));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        let id = ChannelId::new(state.next_id());
+        let data = ChannelData {
+            id,
+            guild_id,
+            name: channel.name,
+            kind: channel.kind,
+            parent_id: channel.parent_id,
+            topic: None,
+            permission_overwrites: channel.permission_overwrites,
+        };
+        state.channels.insert(id, data.clone());
+        Ok(data)
+    }
+
+    async fn edit_channel(&self, channel_id: ChannelId, edit: ChannelEdit) -> Result<ChannelData> {
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        let channel = state.channel_mut(channel_id)?;
+        if let Some(name) = edit.name {
+            channel.name = name;
+        }
+        if let Some(topic) = edit.topic {
+            channel.topic = Some(topic).filter(|t| !t.is_empty());
+        }
+        if let Some(parent_id) = edit.parent_id {
+            channel.parent_id = Some(parent_id);
+        }
+        if let Some(overwrites) = edit.permission_overwrites {
+            channel.permission_overwrites = overwrites;
+        }
+        Ok(channel.clone())
+    }
+
+    async fn delete_channel(&self, channel_id: ChannelId) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        state
+            .channels
+            .remove(&channel_id)
+            .ok_or_else(|| unknown("Channel", channel_id))?;
+        state.messages.remove(&channel_id);
+        // Threads go with their channel
+        state.channels.retain(|_, c| {
+            !(c.kind == ChannelType::PublicThread && c.parent_id == Some(channel_id))
+        });
+        Ok(())
+    }
+
+    async fn history(&self, channel_id: ChannelId) -> Result<Vec<MessageData>> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.messages_mut(channel_id)?.clone())
+    }
+
+    async fn threads(&self, channel_id: ChannelId) -> Result<Vec<ChannelData>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .channels
+            .values()
+            .filter(|c| c.kind == ChannelType::PublicThread && c.parent_id == Some(channel_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn count_messages(&self, channel_id: ChannelId, limit: usize) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.messages_mut(channel_id)?.len().min(limit))
+    }
+
+    async fn send_embeds(
+        &self,
+        channel_id: ChannelId,
+        embeds: Vec<EmbedData>,
+    ) -> Result<MessageId> {
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        let id = MessageId::new(state.next_id());
+        state.messages_mut(channel_id)?.push(MessageData {
+            id,
+            author_id: UserId::new(BOT_USER_ID),
+            author_name: "bot".to_string(),
+            timestamp: Timestamp::now(),
+            content: String::new(),
+            embeds,
+            attachments: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    async fn edit_embeds(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        embeds: Vec<EmbedData>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        let message = state
+            .messages_mut(channel_id)?
+            .iter_mut()
+            .find(|m| m.id == message_id)
+            .ok_or_else(|| unknown("Message", message_id))?;
+        message.embeds = embeds;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel_id: ChannelId, message_id: MessageId) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.write_calls += 1;
+        let messages = state.messages_mut(channel_id)?;
+        let index = messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| unknown("Message", message_id))?;
+        messages.remove(index);
+        Ok(())
+    }
+
+    async fn send_files(
+        &self,
+        channel_id: ChannelId,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<MessageId> {
+        let mut state = self.state.lock().unwrap();
+        state.check_upload(&files)?;
+        state.write_calls += 1;
+        let id = MessageId::new(state.next_id());
+        let attachments = files
+            .iter()
+            .map(|f| AttachmentData {
+                filename: f.name.clone(),
+                url: format!("https://cdn.example/{}", f.name),
+                size: f.data.len() as u32,
+                content_type: None,
+            })
+            .collect();
+        state.messages_mut(channel_id)?.push(MessageData {
+            id,
+            author_id: UserId::new(BOT_USER_ID),
+            author_name: "bot".to_string(),
+            timestamp: Timestamp::now(),
+            content: content.to_string(),
+            embeds: Vec::new(),
+            attachments,
+        });
+        state
+            .files
+            .extend(files.into_iter().map(|f| (Some(channel_id), f)));
+        Ok(id)
+    }
+
+    async fn respond(&self, _interaction: &InteractionHandle, content: &str) -> Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push(content.to_string());
+        Ok(())
+    }
+
+    async fn edit_response(&self, _interaction: &InteractionHandle, content: &str) -> Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push(content.to_string());
+        Ok(())
+    }
+
+    async fn edit_response_with_buttons(
+        &self,
+        _interaction: &InteractionHandle,
+        content: &str,
+        buttons: Vec<ButtonData>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.responses.push(content.to_string());
+        state.buttons = buttons;
+        Ok(())
+    }
+
+    async fn edit_response_with_files(
+        &self,
+        _interaction: &InteractionHandle,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.check_upload(&files)?;
+        state.responses.push(content.to_string());
+        state.files.extend(files.into_iter().map(|f| (None, f)));
+        Ok(())
+    }
+
+    async fn update_message(&self, _interaction: &InteractionHandle, content: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.responses.push(content.to_string());
+        state.buttons.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{
+    Builder, ChannelId, CreateActionRow, CreateAttachment, CreateButton, CreateChannel,
+    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    EditChannel, EditInteractionResponse, EditMessage, GetMessages, GuildChannel, GuildId, Http,
+    Message, MessageId, Timestamp, UserId,
+};
+use serenity::async_trait;
+use std::sync::Arc;
+
+use super::{
+    AttachmentData, Backend, ButtonData, ChannelData, ChannelEdit, EmbedData, FileData,
+    InteractionHandle, MemberData, MessageData, NewChannel, RoleData,
+};
+
+/// Most messages Discord returns per request
+const MESSAGES_PER_PAGE: u8 = 100;
+
+/// Most archived threads Discord returns per request
+const MAX_ARCHIVED_THREADS: u64 = 100;
+
+/// [`Backend`] talking to Discord through serenity's HTTP client
+pub struct SerenityBackend {
+    http: Arc<Http>,
+}
+
+impl SerenityBackend {
+    pub fn new(http: Arc<Http>) -> Self {
+        Self { http }
+    }
+}
+
+impl From<GuildChannel> for ChannelData {
+    fn from(channel: GuildChannel) -> Self {
+        Self {
+            id: channel.id,
+            guild_id: channel.guild_id,
+            name: channel.name,
+            kind: channel.kind,
+            parent_id: channel.parent_id,
+            topic: channel.topic,
+            permission_overwrites: channel.permission_overwrites,
+        }
+    }
+}
+
+impl From<Message> for MessageData {
+    fn from(message: Message) -> Self {
+        Self {
+            id: message.id,
+            author_id: message.author.id,
+            author_name: message.author.name,
+            timestamp: message.timestamp,
+            content: message.content,
+            embeds: message
+                .embeds
+                .into_iter()
+                .map(|e| EmbedData {
+                    description: e.description.unwrap_or_default(),
+                    color: e.colour.map(|c| c.0).unwrap_or_default(),
+                })
+                .collect(),
+            attachments: message
+                .attachments
+                .into_iter()
+                .map(|a| AttachmentData {
+                    filename: a.filename,
+                    url: a.url,
+                    size: a.size,
+                    content_type: a.content_type,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn create_embed(embed: EmbedData) -> CreateEmbed {
+    CreateEmbed::new()
+        .description(embed.description)
+        .color(embed.color)
+        .timestamp(Timestamp::now())
+}
+
+fn create_attachment(file: FileData) -> CreateAttachment {
+    CreateAttachment::bytes(file.data, file.name)
+}
+
+#[async_trait]
+impl Backend for SerenityBackend {
+    async fn channel(&self, channel_id: ChannelId) -> Result<ChannelData> {
+        let channel = channel_id
+            .to_channel(&self.http)
+            .await?
+            .guild()
+            .context("Channel is not a guild channel")?;
+        Ok(channel.into())
+    }
+
+    async fn guild_channels(&self, guild_id: GuildId) -> Result<Vec<ChannelData>> {
+        let channels = guild_id.channels(&self.http).await?;
+        Ok(channels.into_values().map(ChannelData::from).collect())
+    }
+
+    async fn guild_roles(&self, guild_id: GuildId) -> Result<Vec<RoleData>> {
+        let roles = guild_id.roles(&self.http).await?;
+        Ok(roles
+            .into_values()
+            .map(|role| RoleData {
+                id: role.id,
+                name: role.name,
+                permissions: role.permissions,
+            })
+            .collect())
+    }
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<MemberData> {
+        let member = guild_id.member(&self.http, user_id).await?;
+        Ok(MemberData {
+            roles: member.roles,
+        })
+    }
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelData> {
+        let mut builder = CreateChannel::new(channel.name)
+            .kind(channel.kind)
+            .permissions(channel.permission_overwrites);
+        if let Some(parent_id) = channel.parent_id {
+            builder = builder.category(parent_id);
+        }
+
+        let channel = guild_id.create_channel(&self.http, builder).await?;
+        Ok(channel.into())
+    }
+
+    async fn edit_channel(&self, channel_id: ChannelId, edit: ChannelEdit) -> Result<ChannelData> {
+        let mut builder = EditChannel::new();
+        if let Some(name) = &edit.name {
+            builder = builder.name(name);
+        }
+        if let Some(topic) = &edit.topic {
+            builder = builder.topic(topic);
+        }
+        if let Some(parent_id) = edit.parent_id {
+            builder = builder.category(parent_id);
+        }
+        if let Some(overwrites) = edit.permission_overwrites {
+            builder = builder.permissions(overwrites);
+        }
+
+        let channel = channel_id.edit(&self.http, builder).await?;
+        Ok(channel.into())
+    }
+
+    async fn delete_channel(&self, channel_id: ChannelId) -> Result<()> {
+        channel_id.delete(&self.http).await?;
+        Ok(())
+    }
+
+    async fn history(&self, channel_id: ChannelId) -> Result<Vec<MessageData>> {
+        let mut messages = Vec::new();
+        let mut before = None;
+        loop {
+            let mut request = GetMessages::new().limit(MESSAGES_PER_PAGE);
+            if let Some(before) = before {
+                request = request.before(before);
+            }
+            let page = channel_id.messages(&self.http, request).await?;
+            let done = page.len() < MESSAGES_PER_PAGE as usize;
+            before = page.last().map(|m| m.id);
+            messages.extend(page.into_iter().map(MessageData::from));
+            if done {
+                break;
+            }
+        }
+        // Pages come newest first
+        messages.reverse();
+        Ok(messages)
+    }
+
+    async fn threads(&self, channel_id: ChannelId) -> Result<Vec<ChannelData>> {
+        let guild_id = self.channel(channel_id).await?.guild_id;
+        let mut threads: Vec<ChannelData> = guild_id
+            .get_active_threads(&self.http)
+            .await?
+            .threads
+            .into_iter()
+            .filter(|t| t.parent_id == Some(channel_id))
+            .map(ChannelData::from)
+            .collect();
+
+        let archived = channel_id
+            .get_archived_public_threads(&self.http, None, Some(MAX_ARCHIVED_THREADS))
+            .await?;
+        if archived.has_more {
+            eprintln!(
+                "Channel {} has more than {} archived threads, only the latest are read",
+                channel_id, MAX_ARCHIVED_THREADS
+            );
+        }
+        threads.extend(archived.threads.into_iter().map(ChannelData::from));
+        Ok(threads)
+    }
+
+    async fn count_messages(&self, channel_id: ChannelId, limit: usize) -> Result<usize> {
+        let mut count = 0;
+        let mut before = None;
+        while count < limit {
+            let mut request = GetMessages::new().limit(MESSAGES_PER_PAGE);
+            if let Some(before) = before {
+                request = request.before(before);
+            }
+            let page = channel_id.messages(&self.http, request).await?;
+            count += page.len();
+            if page.len() < MESSAGES_PER_PAGE as usize {
+                break;
+            }
+            before = page.last().map(|m| m.id);
+        }
+        Ok(count.min(limit))
+    }
+
+    async fn send_embeds(
+        &self,
+        channel_id: ChannelId,
+        embeds: Vec<EmbedData>,
+    ) -> Result<MessageId> {
+        let message = CreateMessage::new().embeds(embeds.into_iter().map(create_embed).collect());
+
+        let message = channel_id.send_message(&self.http, message).await?;
+        Ok(message.id)
+    }
+
+    async fn edit_embeds(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        embeds: Vec<EmbedData>,
+    ) -> Result<()> {
+        let message = EditMessage::new().embeds(embeds.into_iter().map(create_embed).collect());
+
+        channel_id
+            .edit_message(&self.http, message_id, message)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel_id: ChannelId, message_id: MessageId) -> Result<()> {
+        channel_id.delete_message(&self.http, message_id).await?;
+        Ok(())
+    }
+
+    async fn send_files(
+        &self,
+        channel_id: ChannelId,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<MessageId> {
+        let message = CreateMessage::new()
+            .content(content)
+            .add_files(files.into_iter().map(create_attachment));
+
+        let message = channel_id.send_message(&self.http, message).await?;
+        Ok(message.id)
+    }
+
+    async fn respond(&self, interaction: &InteractionHandle, content: &str) -> Result<()> {
+        let response = CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        );
+        response
+            .execute(&self.http, (interaction.id, &interaction.token))
+            .await?;
+        Ok(())
+    }
+
+    async fn edit_response(&self, interaction: &InteractionHandle, content: &str) -> Result<()> {
+        EditInteractionResponse::new()
+            .content(content)
+            .execute(&self.http, &interaction.token)
+            .await?;
+        Ok(())
+    }
+
+    async fn edit_response_with_buttons(
+        &self,
+        interaction: &InteractionHandle,
+        content: &str,
+        buttons: Vec<ButtonData>,
+    ) -> Result<()> {
+        let buttons = buttons
+            .into_iter()
+            .map(|b| CreateButton::new(b.custom_id).label(b.label).style(b.style))
+            .collect();
+        EditInteractionResponse::new()
+            .content(content)
+            .components(vec![CreateActionRow::Buttons(buttons)])
+            .execute(&self.http, &interaction.token)
+            .await?;
+        Ok(())
+    }
+
+    async fn edit_response_with_files(
+        &self,
+        interaction: &InteractionHandle,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<()> {
+        let mut response = EditInteractionResponse::new().content(content);
+        for file in files {
+            response = response.new_attachment(create_attachment(file));
+        }
+        response.execute(&self.http, &interaction.token).await?;
+        Ok(())
+    }
+
+    async fn update_message(&self, interaction: &InteractionHandle, content: &str) -> Result<()> {
+        let response = CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(Vec::new()),
+        );
+        response
+            .execute(&self.http, (interaction.id, &interaction.token))
+            .await?;
+        Ok(())
+    }
+}
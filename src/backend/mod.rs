@@ -0,0 +1,375 @@
+use anyhow::Result;
+use serde::Serialize;
+use serenity::all::{
+    ButtonStyle, ChannelId, ChannelType, CommandInteraction, ComponentInteraction, GuildId,
+    InteractionId, MessageId, PermissionOverwrite, Permissions, ResolvedOption, ResolvedValue,
+    RoleId, Timestamp, UserId,
+};
+use serenity::async_trait;
+use std::fmt;
+
+pub mod fake;
+pub mod live;
+
+pub use fake::FakeGuild;
+pub use live::SerenityBackend;
+
+/// Error of a request whose target does not exist, such as a deleted channel
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Whether a request failed because its target does not exist (Discord's 404), as
+/// opposed to missing access or a temporary failure
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    if error.downcast_ref::<NotFound>().is_some() {
+        return true;
+    }
+    match error.downcast_ref::<serenity::Error>() {
+        Some(serenity::Error::Http(e)) => e.status_code().map(|s| s.as_u16()) == Some(404),
+        _ => false,
+    }
+}
+
+/// Error of an upload larger than Discord accepts
+#[derive(Debug)]
+pub struct TooLarge(pub String);
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+/// Whether a request was refused for its size (Discord's 413), so it may pass once
+/// split into smaller ones
+pub fn is_too_large(error: &anyhow::Error) -> bool {
+    if error.downcast_ref::<TooLarge>().is_some() {
+        return true;
+    }
+    match error.downcast_ref::<serenity::Error>() {
+        Some(serenity::Error::Http(e)) => e.status_code().map(|s| s.as_u16()) == Some(413),
+        _ => false,
+    }
+}
+
+/// Snapshot of a guild channel
+#[derive(Debug, Clone)]
+pub struct ChannelData {
+    pub id: ChannelId,
+    pub guild_id: GuildId,
+    pub name: String,
+    pub kind: ChannelType,
+    pub parent_id: Option<ChannelId>,
+    pub topic: Option<String>,
+    pub permission_overwrites: Vec<PermissionOverwrite>,
+}
+
+/// Channel to be created in a guild
+#[derive(Debug, Clone)]
+pub struct NewChannel {
+    pub name: String,
+    pub kind: ChannelType,
+    pub parent_id: Option<ChannelId>,
+    pub permission_overwrites: Vec<PermissionOverwrite>,
+}
+
+/// Changes to apply to an existing channel, fields left to `None` are kept
+#[derive(Debug, Clone, Default)]
+pub struct ChannelEdit {
+    pub name: Option<String>,
+    pub topic: Option<String>,
+    /// Category to move the channel to
+    pub parent_id: Option<ChannelId>,
+    /// Replaces every permission overwrite of the channel
+    pub permission_overwrites: Option<Vec<PermissionOverwrite>>,
+}
+
+/// Embed sent by the bot
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbedData {
+    pub description: String,
+    pub color: u32,
+}
+
+/// A message read from a channel
+#[derive(Debug, Clone)]
+pub struct MessageData {
+    pub id: MessageId,
+    pub author_id: UserId,
+    pub author_name: String,
+    pub timestamp: Timestamp,
+    pub content: String,
+    pub embeds: Vec<EmbedData>,
+    pub attachments: Vec<AttachmentData>,
+}
+
+/// A file attached to a message, without its content
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttachmentData {
+    pub filename: String,
+    pub url: String,
+    pub size: u32,
+    pub content_type: Option<String>,
+}
+
+/// A file uploaded by the bot
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileData {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// A button attached to an interaction reply
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButtonData {
+    /// `<command name>:<action>`, routed back to the command when clicked
+    pub custom_id: String,
+    pub label: String,
+    pub style: ButtonStyle,
+}
+
+/// What is needed to answer an interaction
+#[derive(Debug, Clone)]
+pub struct InteractionHandle {
+    pub id: InteractionId,
+    pub token: String,
+}
+
+/// Guild member that invoked a command
+#[derive(Debug, Clone)]
+pub struct MemberData {
+    pub roles: Vec<RoleId>,
+}
+
+/// A guild role and its server-wide permissions
+#[derive(Debug, Clone)]
+pub struct RoleData {
+    pub id: RoleId,
+    pub name: String,
+    pub permissions: Permissions,
+}
+
+/// Value of a slash command option
+#[derive(Debug, Clone)]
+pub enum OptionValue {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    User(UserId),
+    Role(RoleId),
+    Channel(ChannelId),
+}
+
+/// A slash command invocation or a click on one of its buttons, independent of the
+/// gateway types
+#[derive(Debug, Clone)]
+pub struct CommandRequest {
+    pub name: String,
+    pub interaction: InteractionHandle,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub user_id: UserId,
+    pub member: Option<MemberData>,
+    /// Subcommand that was used, e.g. `add` in `/owners add`
+    pub subcommand: Option<String>,
+    /// Options of the command, or of its subcommand when one was used
+    pub options: Vec<(String, OptionValue)>,
+    /// Action of the clicked button, the part of its custom id after the command name
+    pub component: Option<String>,
+}
+
+impl CommandRequest {
+    pub fn from_interaction(command: &CommandInteraction) -> Self {
+        let mut options = command.data.options();
+        let mut subcommand = None;
+        if let Some(ResolvedOption {
+            name,
+            value: ResolvedValue::SubCommand(inner),
+            ..
+        }) = options.first()
+        {
+            subcommand = Some(name.to_string());
+            options = inner.clone();
+        }
+
+        let options = options
+            .into_iter()
+            .filter_map(|option| {
+                let value = match option.value {
+                    ResolvedValue::String(s) => OptionValue::String(s.to_string()),
+                    ResolvedValue::Integer(i) => OptionValue::Integer(i),
+                    ResolvedValue::Boolean(b) => OptionValue::Boolean(b),
+                    ResolvedValue::User(user, _) => OptionValue::User(user.id),
+                    ResolvedValue::Role(role) => OptionValue::Role(role.id),
+                    ResolvedValue::Channel(channel) => OptionValue::Channel(channel.id),
+                    _ => return None,
+                };
+                Some((option.name.to_string(), value))
+            })
+            .collect();
+
+        Self {
+            name: command.data.name.clone(),
+            interaction: InteractionHandle {
+                id: command.id,
+                token: command.token.clone(),
+            },
+            guild_id: command.guild_id,
+            channel_id: command.channel_id,
+            user_id: command.user.id,
+            member: command.member.as_ref().map(|m| MemberData {
+                roles: m.roles.clone(),
+            }),
+            subcommand,
+            options,
+            component: None,
+        }
+    }
+
+    /// Request for a button click, routed to the command named in its custom id
+    pub fn from_component(component: &ComponentInteraction) -> Self {
+        let custom_id = &component.data.custom_id;
+        let (name, action) = custom_id.split_once(':').unwrap_or((custom_id, ""));
+
+        Self {
+            name: name.to_string(),
+            interaction: InteractionHandle {
+                id: component.id,
+                token: component.token.clone(),
+            },
+            guild_id: component.guild_id,
+            channel_id: component.channel_id,
+            user_id: component.user.id,
+            member: component.member.as_ref().map(|m| MemberData {
+                roles: m.roles.clone(),
+            }),
+            subcommand: None,
+            options: Vec::new(),
+            component: Some(action.to_string()),
+        }
+    }
+
+    /// String value of the option called `name`, if it was given
+    pub fn string_option(&self, name: &str) -> Option<&str> {
+        self.options.iter().find_map(|(n, v)| match v {
+            OptionValue::String(s) if n == name => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// User value of the option called `name`, if it was given
+    pub fn user_option(&self, name: &str) -> Option<UserId> {
+        self.options.iter().find_map(|(n, v)| match v {
+            OptionValue::User(u) if n == name => Some(*u),
+            _ => None,
+        })
+    }
+
+    /// Role value of the option called `name`, if it was given
+    pub fn role_option(&self, name: &str) -> Option<RoleId> {
+        self.options.iter().find_map(|(n, v)| match v {
+            OptionValue::Role(r) if n == name => Some(*r),
+            _ => None,
+        })
+    }
+
+    /// Channel value of the option called `name`, if it was given
+    pub fn channel_option(&self, name: &str) -> Option<ChannelId> {
+        self.options.iter().find_map(|(n, v)| match v {
+            OptionValue::Channel(c) if n == name => Some(*c),
+            _ => None,
+        })
+    }
+
+    /// Boolean value of the option called `name`, if it was given
+    pub fn bool_option(&self, name: &str) -> Option<bool> {
+        self.options.iter().find_map(|(n, v)| match v {
+            OptionValue::Boolean(b) if n == name => Some(*b),
+            _ => None,
+        })
+    }
+}
+
+/// Every Discord operation the bot performs, so commands can run against a live
+/// guild through serenity or against an in-memory [`FakeGuild`]
+#[async_trait]
+pub trait Backend: Send + Sync {
+    async fn channel(&self, channel_id: ChannelId) -> Result<ChannelData>;
+
+    async fn guild_channels(&self, guild_id: GuildId) -> Result<Vec<ChannelData>>;
+
+    /// Every role of a guild, including @everyone
+    async fn guild_roles(&self, guild_id: GuildId) -> Result<Vec<RoleData>>;
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<MemberData>;
+
+    async fn create_channel(&self, guild_id: GuildId, channel: NewChannel) -> Result<ChannelData>;
+
+    async fn edit_channel(&self, channel_id: ChannelId, edit: ChannelEdit) -> Result<ChannelData>;
+
+    async fn delete_channel(&self, channel_id: ChannelId) -> Result<()>;
+
+    /// Every message of a channel, oldest first
+    async fn history(&self, channel_id: ChannelId) -> Result<Vec<MessageData>>;
+
+    /// Active and archived public threads started in a channel
+    async fn threads(&self, channel_id: ChannelId) -> Result<Vec<ChannelData>>;
+
+    /// Number of messages in a channel, counting at most `limit`
+    async fn count_messages(&self, channel_id: ChannelId, limit: usize) -> Result<usize>;
+
+    async fn send_embeds(&self, channel_id: ChannelId, embeds: Vec<EmbedData>)
+        -> Result<MessageId>;
+
+    async fn edit_embeds(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        embeds: Vec<EmbedData>,
+    ) -> Result<()>;
+
+    async fn delete_message(&self, channel_id: ChannelId, message_id: MessageId) -> Result<()>;
+
+    /// Post a message with attached files
+    async fn send_files(
+        &self,
+        channel_id: ChannelId,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<MessageId>;
+
+    /// Send the initial ephemeral reply to an interaction
+    async fn respond(&self, interaction: &InteractionHandle, content: &str) -> Result<()>;
+
+    /// Replace the content of the initial reply to an interaction
+    async fn edit_response(&self, interaction: &InteractionHandle, content: &str) -> Result<()>;
+
+    /// Replace the content of the initial reply to an interaction and add a row of buttons
+    async fn edit_response_with_buttons(
+        &self,
+        interaction: &InteractionHandle,
+        content: &str,
+        buttons: Vec<ButtonData>,
+    ) -> Result<()>;
+
+    /// Replace the content of the initial reply to an interaction and attach files to it
+    async fn edit_response_with_files(
+        &self,
+        interaction: &InteractionHandle,
+        content: &str,
+        files: Vec<FileData>,
+    ) -> Result<()>;
+
+    /// Answer a button click by replacing the message it belongs to, removing its buttons
+    async fn update_message(&self, interaction: &InteractionHandle, content: &str) -> Result<()>;
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use serenity::all::{PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, UserId};
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::naming::MAX_NAME_LENGTH;
+use crate::utils::update_channel_list;
+
+/// Marks the name of an archived channel
+pub const ARCHIVE_PREFIX: &str = "archived-";
+
+pub struct Archive;
+
+#[async_trait]
+impl SlashCommand for Archive {
+    fn name(&self) -> &'static str {
+        "archive"
+    }
+
+    fn description(&self) -> &'static str {
+        "Move the channel to the archive, read-only."
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        archive_channel(cx).await
+    }
+}
+
+/// Name of a channel once archived, prefixed and kept within Discord's length limit
+pub fn archived_name(name: &str) -> String {
+    if name.starts_with(ARCHIVE_PREFIX) {
+        return name.to_string();
+    }
+    format!("{}{}", ARCHIVE_PREFIX, name)
+        .chars()
+        .take(MAX_NAME_LENGTH)
+        .collect()
+}
+
+/// Permissions nobody keeps in an archived channel: posting, in threads and forum posts
+/// too, and joining voice channels
+const ARCHIVED_DENY: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::CONNECT)
+    .union(Permissions::SPEAK);
+
+/// Overwrites of an archived channel: every overwrite, including @everyone and those of
+/// the owners, contributors and invited members, the `posters`, denies posting
+fn archived_overwrites(
+    overwrites: &[PermissionOverwrite],
+    posters: &[PermissionOverwriteType],
+) -> Vec<PermissionOverwrite> {
+    let mut overwrites = overwrites.to_vec();
+    for &kind in posters {
+        if !overwrites.iter().any(|o| o.kind == kind) {
+            overwrites.push(PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: Permissions::empty(),
+                kind,
+            });
+        }
+    }
+    for overwrite in &mut overwrites {
+        overwrite.allow -= ARCHIVED_DENY;
+        overwrite.deny |= ARCHIVED_DENY;
+    }
+    overwrites
+}
+
+/// Move the current channel to the archive category, read-only for its owner and out of
+/// the channel list. Used by /archive and by /delete when the server archives instead.
+pub async fn archive_channel(cx: CommandContext<'_>) -> Result<()> {
+    let CommandContext {
+        backend: ctx,
+        request: command,
+        config,
+        registry,
+        ..
+    } = cx;
+    let channel_id = command.channel_id.get();
+
+    let Some(archive_category) = config.archive_category_id() else {
+        ctx.respond(
+            &command.interaction,
+            "Archiving is not enabled in this server!",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    ctx.respond(&command.interaction, "Archiving channel...")
+        .await?;
+
+    let channel = ctx.channel(command.channel_id).await?;
+    let mut edit = ChannelEdit {
+        name: Some(archived_name(&channel.name)),
+        parent_id: Some(archive_category),
+        ..Default::default()
+    };
+    // @everyone first, it may have no overwrite yet
+    let everyone = PermissionOverwriteType::Role(RoleId::new(channel.guild_id.get()));
+    let mut posters = vec![everyone];
+    if let Some(record) = registry.get(channel_id).await {
+        posters.extend(
+            record
+                .owner_ids()
+                .into_iter()
+                .map(|id| PermissionOverwriteType::Member(UserId::new(id)))
+                .chain(record.contributors.iter().map(|c| c.overwrite_kind()))
+                .chain(
+                    record
+                        .invited
+                        .iter()
+                        .map(|&id| PermissionOverwriteType::Member(UserId::new(id))),
+                ),
+        );
+    }
+    edit.permission_overwrites = Some(archived_overwrites(
+        &channel.permission_overwrites,
+        &posters,
+    ));
+
+    // Unarchiving puts the overwrites back as they were
+    let previous = channel.permission_overwrites;
+    let channel = ctx.edit_channel(command.channel_id, edit).await?;
+    registry
+        .record_archived(channel_id, &channel.name, command.user_id.get(), previous)
+        .await?;
+
+    // The channel left the category, so it drops out of the list
+    update_channel_list(ctx, config, registry).await?;
+
+    send_action_embed(
+        ctx,
+        config.log_channel_id(),
+        format!("[{}] archived", channel.name).as_str(),
+        command.user_id.get(),
+        EmbedColor::Blue,
+        None,
+    )
+    .await?;
+
+    ctx.edit_response(
+        &command.interaction,
+        &format!("Channel archived as #{}.", channel.name),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::registry::Contributor;
+use crate::utils::record_overwrites;
+
+pub struct Contributors;
+
+#[async_trait]
+impl SlashCommand for Contributors {
+    fn name(&self) -> &'static str {
+        "contributors"
+    }
+
+    fn description(&self) -> &'static str {
+        "Manage the members and roles allowed to post in the channel."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Allow a member or role to post",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Mentionable,
+                    "contributor",
+                    "Member or role",
+                )
+                .required(true),
+            ),
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Stop a member or role from posting",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Mentionable,
+                    "contributor",
+                    "Member or role",
+                )
+                .required(true),
+            ),
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "Show who can post besides the owners",
+            ),
+        ]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let guild_id = command
+            .guild_id
+            .context("This command can only be used in a server")?;
+        let record = registry
+            .get(channel_id)
+            .await
+            .context("Channel has no record")?;
+
+        let adding = match command.subcommand.as_deref() {
+            Some("add") => true,
+            Some("remove") => false,
+            Some("list") => {
+                let reply = if record.contributors.is_empty() {
+                    "This channel has no contributors.".to_string()
+                } else {
+                    let mentions: Vec<String> =
+                        record.contributors.iter().map(|c| c.mention()).collect();
+                    format!("Contributors of this channel: {}", mentions.join(", "))
+                };
+                ctx.respond(&command.interaction, &reply).await?;
+                return Ok(());
+            }
+            _ => {
+                ctx.respond(
+                    &command.interaction,
+                    "Use /contributors add, /contributors remove or /contributors list!",
+                )
+                .await?;
+                return Ok(());
+            }
+        };
+
+        let contributor = match (
+            command.user_option("contributor"),
+            command.role_option("contributor"),
+        ) {
+            (Some(user), _) => Contributor::User(user.get()),
+            (None, Some(role)) if role.get() != guild_id.get() => Contributor::Role(role.get()),
+            _ => {
+                ctx.respond(&command.interaction, "Member or role is not valid!")
+                    .await?;
+                return Ok(());
+            }
+        };
+        let mention = contributor.mention();
+
+        let refusal = match contributor {
+            Contributor::User(id) if adding && record.is_owned_by(id) => {
+                Some(format!("{} already owns this channel!", mention))
+            }
+            _ if adding && record.contributors.contains(&contributor) => {
+                Some(format!("{} is already a contributor!", mention))
+            }
+            _ if !adding && !record.contributors.contains(&contributor) => {
+                Some(format!("{} is not a contributor of this channel!", mention))
+            }
+            _ => None,
+        };
+        if let Some(refusal) = refusal {
+            ctx.respond(&command.interaction, &refusal).await?;
+            return Ok(());
+        }
+
+        ctx.respond(&command.interaction, "Updating contributors...")
+            .await?;
+
+        if adding {
+            registry
+                .record_contributor_added(channel_id, contributor)
+                .await?;
+        } else {
+            registry
+                .record_contributor_removed(channel_id, contributor)
+                .await?;
+        }
+
+        let record = registry
+            .get(channel_id)
+            .await
+            .context("Channel has no record")?;
+        let channel = ctx.channel(command.channel_id).await?;
+        let kind = contributor.overwrite_kind();
+        let overwrites = channel
+            .permission_overwrites
+            .into_iter()
+            .filter(|o| o.kind != kind)
+            .collect();
+        ctx.edit_channel(
+            command.channel_id,
+            ChannelEdit {
+                permission_overwrites: Some(record_overwrites(overwrites, &record, config)),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let (action, color, reply) = if adding {
+            (
+                format!("contributor {} added", mention),
+                EmbedColor::Green,
+                format!("{} can now post in this channel!", mention),
+            )
+        } else {
+            (
+                format!("contributor {} removed", mention),
+                EmbedColor::Yellow,
+                format!("{} can no longer post in this channel!", mention),
+            )
+        };
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            &action,
+            command.user_id.get(),
+            color,
+            Some(command.channel_id),
+        )
+        .await?;
+
+        ctx.edit_response(&command.interaction, &reply).await?;
+
+        Ok(())
+    }
+}
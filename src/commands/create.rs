@@ -1,98 +1,210 @@
 use anyhow::{Context as AnyhowContext, Result};
-use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
-};
+use serenity::all::{ChannelType, CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
 
-use crate::config::Config;
+use super::{CommandContext, Permission, SlashCommand};
 use crate::embed::{send_action_embed, EmbedColor};
-use crate::utils::{create_channel_with_permissions, has_special_role, update_channel_list};
+use crate::naming::{check_channel_name, describe_name};
+use crate::utils::{
+    create_channel_with_permissions, has_manager_role, quota_reached, update_channel_list,
+    ChannelSettings,
+};
 
-pub fn register() -> CreateCommand {
-    let name = "create";
-    let description = "Create a new channel.";
+pub struct Create;
+
+#[async_trait]
+impl SlashCommand for Create {
+    fn name(&self) -> &'static str {
+        "create"
+    }
 
-    println!(" > /{} - {}", name, description);
+    fn description(&self) -> &'static str {
+        "Create a new channel."
+    }
 
-    CreateCommand::new(name)
-        .description(description)
-        .add_option(
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
             CreateCommandOption::new(CommandOptionType::String, "name", "Name of the channel")
                 .required(true),
-        )
-}
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "template",
+                "Permission template from the server configuration",
+            ),
+            CreateCommandOption::new(CommandOptionType::String, "type", "Kind of channel")
+                .add_string_choice("Text", "text")
+                .add_string_choice("Announcement", "news")
+                .add_string_choice("Forum", "forum")
+                .add_string_choice("Voice", "voice"),
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "private",
+                "Hide the channel from everyone but its owners and invited members",
+            ),
+        ]
+    }
 
-pub async fn run(
-    ctx: &Context,
-    command: &CommandInteraction,
-    config: &Config,
-) -> Result<()> {
-    let guild_id = command
-        .guild_id
-        .context("This command can only be used in a server")?;
-
-    let member = command
-        .member
-        .as_ref()
-        .context("Unable to get member data")?;
-
-    if !has_special_role(member, config).await {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("You do not have permission to use this command!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
     }
 
-    let options = &command.data.options();
-    let channel_name = if let Some(ResolvedOption {
-        value: ResolvedValue::String(name),
-        ..
-    }) = options.first()
-    {
-        name
-    } else {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("Channel name is not valid!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
-    };
-
-    let response = CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content("Creating channel...")
-            .ephemeral(true),
-    );
-    command.create_response(&ctx.http, response).await?;
-
-    let channel =
-        create_channel_with_permissions(ctx, config, channel_name, command.user.id, guild_id)
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            bot_user_id,
+            ..
+        } = cx;
+
+        let guild_id = command
+            .guild_id
+            .context("This command can only be used in a server")?;
+
+        let requested = if let Some(name) = command.string_option("name") {
+            name
+        } else {
+            ctx.respond(&command.interaction, "Channel name is not valid!")
+                .await?;
+            return Ok(());
+        };
+
+        let kind = match command.string_option("type") {
+            None | Some("text") => ChannelType::Text,
+            Some("news") | Some("announcement") => ChannelType::News,
+            Some("forum") => ChannelType::Forum,
+            Some("voice") => ChannelType::Voice,
+            Some(other) => {
+                ctx.respond(
+                    &command.interaction,
+                    &format!("Unknown channel type `{}`!", other),
+                )
+                .await?;
+                return Ok(());
+            }
+        };
+
+        let private = command.bool_option("private").unwrap_or(false);
+
+        let template = match command.string_option("template") {
+            Some(name) => match config.template(name) {
+                Some(template) => Some((name, template)),
+                None => {
+                    let message = if config.templates.is_empty() {
+                        "This server has no permission templates!".to_string()
+                    } else {
+                        let names: Vec<&str> =
+                            config.templates.keys().map(String::as_str).collect();
+                        format!(
+                            "Unknown template `{}`! Available templates: {}",
+                            name,
+                            names.join(", ")
+                        )
+                    };
+                    ctx.respond(&command.interaction, &message).await?;
+                    return Ok(());
+                }
+            },
+            None => None,
+        };
+
+        let member = command
+            .member
+            .as_ref()
+            .context("Unable to get member data")?;
+
+        // Managers are not limited by the quota or the naming rules
+        let is_manager = has_manager_role(member, config).await;
+        if let Some(quota) =
+            quota_reached(ctx, config, registry, command.user_id.get(), member).await?
+        {
+            let message = if quota.owned.is_empty() {
+                "You are not allowed to create channels!".to_string()
+            } else {
+                let owned: Vec<String> =
+                    quota.owned.iter().map(|id| format!("<#{}>", id)).collect();
+                format!(
+                    "You already own {} channel(s), the limit is {}: {}. Delete one before creating another.",
+                    owned.len(),
+                    quota.limit,
+                    owned.join(", ")
+                )
+            };
+            ctx.respond(&command.interaction, &message).await?;
+            return Ok(());
+        }
+
+        let channel_name =
+            match check_channel_name(ctx, config, registry, requested, kind, !is_manager, None)
+                .await?
+            {
+                Ok(name) => name,
+                Err(e) => {
+                    ctx.respond(&command.interaction, &e.to_string()).await?;
+                    return Ok(());
+                }
+            };
+
+        ctx.respond(
+            &command.interaction,
+            &format!(
+                "Creating channel {}...",
+                describe_name(requested, &channel_name)
+            ),
+        )
+        .await?;
+
+        let channel = create_channel_with_permissions(
+            ctx,
+            config,
+            &channel_name,
+            command.user_id,
+            bot_user_id,
+            guild_id,
+            ChannelSettings {
+                kind,
+                template: template.map(|(_, t)| t),
+                private,
+            },
+        )
+        .await?;
+
+        registry
+            .record_created(
+                guild_id.get(),
+                channel.id.get(),
+                command.user_id.get(),
+                &channel.name,
+                template.map(|(name, _)| name),
+            )
             .await?;
 
-    update_channel_list(ctx, config).await?;
-
-    send_action_embed(
-        ctx,
-        config.log_channel_id(),
-        "created",
-        command.user.id.get(),
-        EmbedColor::Green,
-        Some(channel.id),
-    )
-    .await?;
-
-    command
-        .edit_response(
-            &ctx.http,
-            serenity::all::EditInteractionResponse::new()
-                .content(format!("Channel created successfully! <#{}>", channel.id)),
+        update_channel_list(ctx, config, registry).await?;
+
+        let mut action = match template {
+            Some((name, _)) => format!("created from template `{}`", name),
+            None => "created".to_string(),
+        };
+        if private {
+            action.push_str(" as a private channel");
+        }
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            &action,
+            command.user_id.get(),
+            EmbedColor::Green,
+            Some(channel.id),
         )
         .await?;
 
-    Ok(())
-}
\ No newline at end of file
+        ctx.edit_response(
+            &command.interaction,
+            &format!("Channel created successfully! <#{}>", channel.id),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,82 +1,211 @@
-use anyhow::{Context as AnyhowContext, Result};
-use serenity::all::{
-    CommandInteraction, Context, CreateCommand, CreateInteractionResponse,
-    CreateInteractionResponseMessage,
-};
+use anyhow::Result;
+use chrono::Utc;
+use serenity::all::{ButtonStyle, ChannelType, CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
 
-use crate::config::Config;
+use super::archive::archive_channel;
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ButtonData;
 use crate::embed::{send_action_embed, EmbedColor};
-use crate::utils::{get_managed_channels, has_special_role, update_channel_list};
+use crate::transcript::save_transcript;
+use crate::trash::move_to_trash;
+use crate::utils::update_channel_list;
 
-pub fn register() -> CreateCommand {
-    let name = "delete";
-    let description = "Delete the channel.";
+/// How long the confirmation buttons stay valid, in seconds
+pub const CONFIRM_TIMEOUT_SECS: i64 = 60;
 
-    println!(" > /{} - {}", name, description);
+/// Messages counted for the confirmation, larger channels show `1000+`
+const MESSAGE_COUNT_LIMIT: usize = 1000;
 
-    CreateCommand::new(name).description(description)
-}
+pub struct Delete;
 
-pub async fn run(
-    ctx: &Context,
-    command: &CommandInteraction,
-    config: &Config,
-) -> Result<()> {
-    let channel_id = command.channel_id.get();
-
-    let member = command
-        .member
-        .as_ref()
-        .context("Unable to get member data")?;
-
-    if !has_special_role(member, config).await {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("You don't have permission to use this command!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+#[async_trait]
+impl SlashCommand for Delete {
+    fn name(&self) -> &'static str {
+        "delete"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete the channel."
     }
 
-    // Check if this channel is managed by the bot
-    let channels = get_managed_channels(ctx, config).await?;
-    let channel_info = channels.iter().find(|c| c.channel_id == channel_id);
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "permanent",
+            "Delete the channel even if this server archives deleted channels",
+        )]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    /// Ask for confirmation, the channel is deleted when the Confirm button is clicked.
+    /// Servers with `archive_on_delete` archive the channel instead, which can be undone.
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        if config.archive_on_delete && !command.bool_option("permanent").unwrap_or(false) {
+            return archive_channel(cx).await;
+        }
+
+        let record = registry.get(channel_id).await;
+        let channel_name = record
+            .as_ref()
+            .and_then(|r| r.name().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string());
+        let creator = match record.map(|r| r.creator_id) {
+            Some(creator_id) if creator_id != 0 => format!("<@{}>", creator_id),
+            _ => "an unknown member".to_string(),
+        };
+
+        // Counting can take several requests, answer before Discord's deadline
+        ctx.respond(&command.interaction, "Checking the channel...")
+            .await?;
 
-    if channel_info.is_none() {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("This channel was not created by the bot and cannot be deleted!")
-                .ephemeral(true),
+        // Forums have no messages of their own, only posts
+        let channel = ctx.channel(command.channel_id).await?;
+        let contents = if channel.kind == ChannelType::Forum {
+            format!("{} post(s)", ctx.threads(command.channel_id).await?.len())
+        } else {
+            let count = ctx
+                .count_messages(command.channel_id, MESSAGE_COUNT_LIMIT)
+                .await?;
+            if count >= MESSAGE_COUNT_LIMIT {
+                format!("{}+ message(s)", MESSAGE_COUNT_LIMIT)
+            } else {
+                format!("{} message(s)", count)
+            }
+        };
+
+        let outcome = if config.trash_category_id.is_some() {
+            format!(
+                "A manager can restore it with /restore for {} day(s).",
+                config.trash_retention_days()
+            )
+        } else {
+            "This cannot be undone.".to_string()
+        };
+        let expires_at = Utc::now().timestamp() + CONFIRM_TIMEOUT_SECS;
+        let content = format!(
+            "Delete **#{}**? It has {} and was created by {}. {}\n\
+             The buttons expire <t:{}:R>.",
+            channel_name, contents, creator, outcome, expires_at
         );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+        let buttons = vec![
+            ButtonData {
+                custom_id: format!("{}:confirm:{}:{}", self.name(), channel_id, expires_at),
+                label: "Delete".to_string(),
+                style: ButtonStyle::Danger,
+            },
+            ButtonData {
+                custom_id: format!("{}:cancel:{}", self.name(), channel_id),
+                label: "Cancel".to_string(),
+                style: ButtonStyle::Secondary,
+            },
+        ];
+
+        ctx.edit_response_with_buttons(&command.interaction, &content, buttons)
+            .await?;
+
+        Ok(())
     }
 
-    let channel_name = channel_info.map(|c| c.name.clone()).unwrap_or_else(|| "unknown".to_string());
-
-    let response = CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content("Deleting channel...")
-            .ephemeral(true),
-    );
-    command.create_response(&ctx.http, response).await?;
-
-    send_action_embed(
-        ctx,
-        config.log_channel_id(),
-        format!("[{}] deleted", channel_name).as_str(),
-        command.user.id.get(),
-        EmbedColor::Red,
-        None,
-    )
-    .await?;
-
-    // Delete the channel
-    command.channel_id.delete(&ctx.http).await?;
-
-    // Update the list (will automatically exclude the deleted channel)
-    update_channel_list(ctx, config).await?;
-
-    Ok(())
-}
\ No newline at end of file
+    async fn handle_component(&self, cx: CommandContext<'_>, action: &str) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let parts: Vec<&str> = action.split(':').collect();
+        let (target, expires_at) = match parts.as_slice() {
+            ["confirm", target, expires_at] => (*target, expires_at.parse().unwrap_or(0)),
+            ["cancel", ..] => {
+                ctx.update_message(&command.interaction, "Deletion cancelled.")
+                    .await?;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        // The buttons only ever act on the channel they were sent in
+        if target != channel_id.to_string() {
+            ctx.update_message(
+                &command.interaction,
+                "This confirmation is not for this channel.",
+            )
+            .await?;
+            return Ok(());
+        }
+        if Utc::now().timestamp() > expires_at {
+            ctx.update_message(
+                &command.interaction,
+                "This confirmation has expired, run /delete again.",
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let channel_name = registry
+            .get(channel_id)
+            .await
+            .and_then(|r| r.name().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        ctx.update_message(&command.interaction, "Deleting channel...")
+            .await?;
+
+        // Servers with a trash keep the channel until it is purged, the transcript is
+        // saved then. Otherwise it is saved now, and the channel is only deleted once
+        // its history is safe.
+        if config.trash_category_id.is_some() {
+            move_to_trash(ctx, config, registry, command.channel_id, command.user_id).await?;
+        } else {
+            if let Err(e) = save_transcript(ctx, command.channel_id, config.log_channel_id()).await
+            {
+                ctx.edit_response(
+                    &command.interaction,
+                    &format!(
+                        "The transcript of this channel could not be saved, so it was not deleted: {}",
+                        e
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            ctx.delete_channel(command.channel_id).await?;
+            registry.record_deleted(channel_id).await?;
+        }
+
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            format!("[{}] deleted", channel_name).as_str(),
+            command.user_id.get(),
+            EmbedColor::Red,
+            None,
+        )
+        .await?;
+
+        // Update the list (will automatically exclude the deleted channel)
+        update_channel_list(ctx, config, registry).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{CommandOptionType, CreateCommandOption, PermissionOverwriteType};
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::utils::{is_private, record_overwrites};
+
+pub struct Invite;
+
+#[async_trait]
+impl SlashCommand for Invite {
+    fn name(&self) -> &'static str {
+        "invite"
+    }
+
+    fn description(&self) -> &'static str {
+        "Let a member into the private channel."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(CommandOptionType::User, "member", "Member to invite")
+                .required(true),
+        ]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        change_membership(cx, true).await
+    }
+}
+
+/// Invite a member into the current private channel or kick them out of it. Used by
+/// /invite and /kick.
+pub async fn change_membership(cx: CommandContext<'_>, inviting: bool) -> Result<()> {
+    let CommandContext {
+        backend: ctx,
+        request: command,
+        config,
+        registry,
+        ..
+    } = cx;
+    let channel_id = command.channel_id.get();
+
+    let guild_id = command
+        .guild_id
+        .context("This command can only be used in a server")?;
+    let Some(user) = command.user_option("member") else {
+        ctx.respond(&command.interaction, "Member is not valid!")
+            .await?;
+        return Ok(());
+    };
+
+    let channel = ctx.channel(command.channel_id).await?;
+    let record = registry
+        .get(channel_id)
+        .await
+        .context("Channel has no record")?;
+    let refusal = if !is_private(&channel) {
+        Some("This channel is not private, everyone can already see it!".to_string())
+    } else if inviting && record.is_owned_by(user.get()) {
+        Some(format!("<@{}> already owns this channel!", user))
+    } else if inviting && record.invited.contains(&user.get()) {
+        Some(format!("<@{}> is already invited!", user))
+    } else if !inviting && !record.invited.contains(&user.get()) {
+        Some(format!("<@{}> was not invited to this channel!", user))
+    } else if inviting && ctx.member(guild_id, user).await.is_err() {
+        Some(format!("<@{}> is not a member of this server!", user))
+    } else {
+        None
+    };
+    if let Some(refusal) = refusal {
+        ctx.respond(&command.interaction, &refusal).await?;
+        return Ok(());
+    }
+
+    ctx.respond(&command.interaction, "Updating channel members...")
+        .await?;
+
+    if inviting {
+        registry.record_invited(channel_id, user.get()).await?;
+    } else {
+        registry.record_kicked(channel_id, user.get()).await?;
+    }
+
+    // A kicked member keeps only the overwrite they may have as a contributor
+    let record = registry
+        .get(channel_id)
+        .await
+        .context("Channel has no record")?;
+    let overwrites = channel
+        .permission_overwrites
+        .into_iter()
+        .filter(|o| o.kind != PermissionOverwriteType::Member(user))
+        .collect();
+    ctx.edit_channel(
+        command.channel_id,
+        ChannelEdit {
+            permission_overwrites: Some(record_overwrites(overwrites, &record, config)),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let (action, color, reply) = if inviting {
+        (
+            format!("<@{}> invited", user),
+            EmbedColor::Green,
+            format!("<@{}> can now see this channel!", user),
+        )
+    } else {
+        (
+            format!("<@{}> kicked", user),
+            EmbedColor::Red,
+            format!("<@{}> can no longer see this channel!", user),
+        )
+    };
+    send_action_embed(
+        ctx,
+        config.log_channel_id(),
+        &action,
+        command.user_id.get(),
+        color,
+        Some(command.channel_id),
+    )
+    .await?;
+
+    ctx.edit_response(&command.interaction, &reply).await?;
+
+    Ok(())
+}
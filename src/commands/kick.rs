@@ -0,0 +1,38 @@
+use anyhow::Result;
+use serenity::all::{CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
+
+use super::invite::change_membership;
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+
+pub struct Kick;
+
+#[async_trait]
+impl SlashCommand for Kick {
+    fn name(&self) -> &'static str {
+        "kick"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove an invited member from the private channel."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(CommandOptionType::User, "member", "Member to remove")
+                .required(true),
+        ]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        change_membership(cx, false).await
+    }
+}
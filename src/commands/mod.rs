@@ -1,5 +1,274 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{CreateCommand, CreateCommandOption, UserId};
+use serenity::async_trait;
+
+use crate::backend::{Backend, CommandRequest, MemberData};
+use crate::config::{GuildConfig, SharedConfig};
+use crate::registry::{ChannelRegistry, ChannelStatus};
+use crate::utils::{has_manager_role, has_special_role, is_managed_channel, is_thread};
+
+pub mod archive;
+pub mod contributors;
 pub mod create;
 pub mod delete;
 pub mod description;
+pub mod invite;
+pub mod kick;
+pub mod owners;
+pub mod reloadconfig;
 pub mod rename;
-pub mod updatelist;
\ No newline at end of file
+pub mod restore;
+pub mod transcript;
+pub mod transfer;
+pub mod unarchive;
+pub mod updatelist;
+
+/// Role a member needs to use a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Everyone,
+    SpecialRole,
+    ManagerRole,
+}
+
+/// Where a command may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelRequirement {
+    Anywhere,
+    /// Inside a channel managed by the bot
+    Managed,
+    /// Inside a managed channel the member owns or co-owns, managers may act on any of them
+    Owned,
+    /// Inside a managed channel the member is the owner of, co-owners are refused and
+    /// managers may act on any of them
+    PrimaryOwned,
+    /// Inside an archived channel the member owns or co-owns, managers may act on any of them
+    Archived,
+}
+
+/// Everything a command runs against
+#[derive(Clone, Copy)]
+pub struct CommandContext<'a> {
+    pub backend: &'a dyn Backend,
+    pub request: &'a CommandRequest,
+    /// Settings of the server the command was used in
+    pub config: &'a GuildConfig,
+    pub registry: &'a ChannelRegistry,
+    pub shared_config: &'a SharedConfig,
+    /// User the bot is logged in as
+    pub bot_user_id: UserId,
+}
+
+/// A slash command: how it is registered, who may use it and what it does
+#[async_trait]
+pub trait SlashCommand: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        Vec::new()
+    }
+
+    fn permission(&self) -> Permission;
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Anywhere
+    }
+
+    /// Run the command once the checks declared above passed
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()>;
+
+    /// Handle a click on one of the buttons the command sent, after the same checks.
+    /// `action` is the button's custom id without the command name.
+    async fn handle_component(&self, _cx: CommandContext<'_>, _action: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn register(&self) -> CreateCommand {
+        println!(" > /{} - {}", self.name(), self.description());
+
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .set_options(self.options())
+    }
+}
+
+/// Every command of the bot, driving both registration and dispatch
+pub struct CommandSet {
+    commands: Vec<Box<dyn SlashCommand>>,
+}
+
+impl Default for CommandSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandSet {
+    pub fn new() -> Self {
+        Self {
+            commands: vec![
+                Box::new(create::Create),
+                Box::new(delete::Delete),
+                Box::new(rename::Rename),
+                Box::new(description::Description),
+                Box::new(archive::Archive),
+                Box::new(unarchive::Unarchive),
+                Box::new(restore::Restore),
+                Box::new(transcript::Transcript),
+                Box::new(transfer::Transfer),
+                Box::new(owners::Owners),
+                Box::new(contributors::Contributors),
+                Box::new(invite::Invite),
+                Box::new(kick::Kick),
+                Box::new(updatelist::UpdateList),
+                Box::new(reloadconfig::ReloadConfig),
+            ],
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn SlashCommand> {
+        self.commands
+            .iter()
+            .find(|c| c.name() == name)
+            .map(|c| c.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.commands.iter().map(|c| c.name()).collect()
+    }
+
+    pub fn register_all(&self) -> Vec<CreateCommand> {
+        self.commands.iter().map(|c| c.register()).collect()
+    }
+
+    /// Run the requested command after checking the member's role, the channel and its owner
+    pub async fn dispatch(&self, cx: CommandContext<'_>) -> Result<()> {
+        let Some(command) = self.get(&cx.request.name) else {
+            return Ok(());
+        };
+
+        let member = cx
+            .request
+            .member
+            .as_ref()
+            .context("Unable to get member data")?;
+
+        let allowed = match command.permission() {
+            Permission::Everyone => true,
+            Permission::SpecialRole => has_special_role(member, cx.config).await,
+            Permission::ManagerRole => has_manager_role(member, cx.config).await,
+        };
+        if !allowed {
+            let refusal = match command.permission() {
+                Permission::ManagerRole => "Only managers can use this command!",
+                _ => "You do not have permission to use this command!",
+            };
+            cx.backend.respond(&cx.request.interaction, refusal).await?;
+            return Ok(());
+        }
+
+        // Used in a thread, such as a forum post, the command acts on the thread's channel
+        let parent_id = if command.channel() == ChannelRequirement::Anywhere {
+            None
+        } else {
+            cx.backend
+                .channel(cx.request.channel_id)
+                .await
+                .ok()
+                .filter(|c| is_thread(c.kind))
+                .and_then(|c| c.parent_id)
+        };
+        let request;
+        let cx = match parent_id {
+            Some(parent_id) => {
+                request = CommandRequest {
+                    channel_id: parent_id,
+                    ..cx.request.clone()
+                };
+                CommandContext {
+                    request: &request,
+                    ..cx
+                }
+            }
+            None => cx,
+        };
+
+        if command.channel() != ChannelRequirement::Anywhere
+            && !self.check_channel(command, cx, member).await?
+        {
+            return Ok(());
+        }
+
+        match &cx.request.component {
+            Some(action) => command.handle_component(cx, action).await,
+            None => command.run(cx).await,
+        }
+    }
+
+    /// Check the channel requirement of `command`, replying with a refusal when it fails
+    async fn check_channel(
+        &self,
+        command: &dyn SlashCommand,
+        cx: CommandContext<'_>,
+        member: &MemberData,
+    ) -> Result<bool> {
+        let channel_id = cx.request.channel_id.get();
+        let refusal = if command.channel() == ChannelRequirement::Archived {
+            let archived = cx
+                .registry
+                .get(channel_id)
+                .await
+                .is_some_and(|r| r.status == ChannelStatus::Archived);
+            (!archived).then_some("This channel is not archived and cannot be used with")
+        } else {
+            let managed =
+                is_managed_channel(cx.backend, cx.config, cx.registry, channel_id).await?;
+            (!managed).then_some("This channel was not created by the bot and cannot be used with")
+        };
+        if let Some(refusal) = refusal {
+            cx.backend
+                .respond(
+                    &cx.request.interaction,
+                    &format!("{} /{}!", refusal, command.name()),
+                )
+                .await?;
+            return Ok(false);
+        }
+
+        if command.channel() != ChannelRequirement::Managed
+            && !has_manager_role(member, cx.config).await
+        {
+            // Reconciled or archived, so the channel has a record
+            let record = cx.registry.get(channel_id).await;
+            let owner_id = record.as_ref().map(|r| r.owner_id()).unwrap_or(0);
+            let user_id = cx.request.user_id.get();
+            let allowed = if command.channel() == ChannelRequirement::PrimaryOwned {
+                owner_id == user_id
+            } else {
+                record.is_some_and(|r| r.is_owned_by(user_id))
+            };
+            if !allowed {
+                let refusal = if owner_id == 0 {
+                    format!(
+                        "This channel has no recorded owner, only a manager can use /{} here!",
+                        command.name()
+                    )
+                } else {
+                    format!(
+                        "This channel belongs to <@{}>, only they or a manager can use /{} here!",
+                        owner_id,
+                        command.name()
+                    )
+                };
+                cx.backend
+                    .respond(&cx.request.interaction, &refusal)
+                    .await?;
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{CommandOptionType, CreateCommandOption, PermissionOverwriteType};
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::utils::{record_overwrites, update_channel_list};
+
+pub struct Owners;
+
+#[async_trait]
+impl SlashCommand for Owners {
+    fn name(&self) -> &'static str {
+        "owners"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add or remove co-owners of the channel."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a co-owner")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::User, "member", "New co-owner")
+                        .required(true),
+                ),
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a co-owner")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::User,
+                        "member",
+                        "Co-owner to remove",
+                    )
+                    .required(true),
+                ),
+        ]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::PrimaryOwned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let guild_id = command
+            .guild_id
+            .context("This command can only be used in a server")?;
+        let adding = match command.subcommand.as_deref() {
+            Some("add") => true,
+            Some("remove") => false,
+            _ => {
+                ctx.respond(&command.interaction, "Use /owners add or /owners remove!")
+                    .await?;
+                return Ok(());
+            }
+        };
+        let Some(user) = command.user_option("member") else {
+            ctx.respond(&command.interaction, "Member is not valid!")
+                .await?;
+            return Ok(());
+        };
+
+        let record = registry
+            .get(channel_id)
+            .await
+            .context("Channel has no record")?;
+        let refusal = if user.get() == record.owner_id() {
+            Some(format!("<@{}> already owns this channel!", user))
+        } else if adding && record.co_owners.contains(&user.get()) {
+            Some(format!(
+                "<@{}> is already a co-owner of this channel!",
+                user
+            ))
+        } else if !adding && !record.co_owners.contains(&user.get()) {
+            Some(format!("<@{}> is not a co-owner of this channel!", user))
+        } else if adding && ctx.member(guild_id, user).await.is_err() {
+            Some(format!("<@{}> is not a member of this server!", user))
+        } else {
+            None
+        };
+        if let Some(refusal) = refusal {
+            ctx.respond(&command.interaction, &refusal).await?;
+            return Ok(());
+        }
+
+        ctx.respond(&command.interaction, "Updating channel owners...")
+            .await?;
+
+        if adding {
+            registry
+                .record_co_owner_added(channel_id, user.get())
+                .await?;
+        } else {
+            registry
+                .record_co_owner_removed(channel_id, user.get())
+                .await?;
+        }
+
+        // Co-owners get their own overwrite, a removed one keeps only the overwrite
+        // they may have as a contributor
+        let record = registry
+            .get(channel_id)
+            .await
+            .context("Channel has no record")?;
+        let channel = ctx.channel(command.channel_id).await?;
+        let overwrites = channel
+            .permission_overwrites
+            .into_iter()
+            .filter(|o| o.kind != PermissionOverwriteType::Member(user))
+            .collect();
+        ctx.edit_channel(
+            command.channel_id,
+            ChannelEdit {
+                permission_overwrites: Some(record_overwrites(overwrites, &record, config)),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        update_channel_list(ctx, config, registry).await?;
+
+        let (action, color, reply) = if adding {
+            (
+                format!("co-owner <@{}> added", user),
+                EmbedColor::Green,
+                format!("<@{}> is now a co-owner of this channel!", user),
+            )
+        } else {
+            (
+                format!("co-owner <@{}> removed", user),
+                EmbedColor::Yellow,
+                format!("<@{}> is no longer a co-owner of this channel!", user),
+            )
+        };
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            &action,
+            command.user_id.get(),
+            color,
+            Some(command.channel_id),
+        )
+        .await?;
+
+        ctx.edit_response(&command.interaction, &reply).await?;
+
+        Ok(())
+    }
+}
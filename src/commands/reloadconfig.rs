@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serenity::async_trait;
+
+use super::{CommandContext, Permission, SlashCommand};
+use crate::reload::reload_and_report;
+
+pub struct ReloadConfig;
+
+#[async_trait]
+impl SlashCommand for ReloadConfig {
+    fn name(&self) -> &'static str {
+        "reloadconfig"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reload the bot configuration file."
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::ManagerRole
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            shared_config: shared,
+            ..
+        } = cx;
+
+        ctx.respond(&command.interaction, "Reloading configuration...")
+            .await?;
+
+        let message = match reload_and_report(ctx, shared, Some(command.user_id.get())).await {
+            Ok(changes) if changes.is_empty() => {
+                "Configuration reloaded, nothing changed.".to_string()
+            }
+            Ok(changes) => format!(
+                "Configuration reloaded with {} change(s), see the log channel.",
+                changes.len()
+            ),
+            Err(e) => format!("Configuration not reloaded, keeping the current one: {}", e),
+        };
+
+        ctx.edit_response(&command.interaction, &message).await?;
+
+        Ok(())
+    }
+}
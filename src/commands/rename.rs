@@ -1,109 +1,123 @@
 use anyhow::{Context as AnyhowContext, Result};
-use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    CreateInteractionResponse, CreateInteractionResponseMessage, EditChannel, ResolvedOption,
-    ResolvedValue,
-};
+use serenity::all::{CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
 
-use crate::config::Config;
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
 use crate::embed::{send_action_embed, EmbedColor};
-use crate::utils::{is_managed_channel, has_special_role, update_channel_list};
+use crate::naming::{check_channel_name, describe_name};
+use crate::utils::{has_manager_role, update_channel_list};
 
-pub fn register() -> CreateCommand {
-    let name = "rename";
-    let description = "Rename the channel.";
+pub struct Rename;
 
-    println!(" > /{} - {}", name, description);
+#[async_trait]
+impl SlashCommand for Rename {
+    fn name(&self) -> &'static str {
+        "rename"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rename the channel."
+    }
 
-    CreateCommand::new(name)
-        .description(description)
-        .add_option(
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
             CreateCommandOption::new(CommandOptionType::String, "name", "New channel name")
                 .required(true),
-        )
-}
+        ]
+    }
 
-pub async fn run(
-    ctx: &Context,
-    command: &CommandInteraction,
-    config: &Config,
-) -> Result<()> {
-    let channel_id = command.channel_id.get();
-
-    let member = command
-        .member
-        .as_ref()
-        .context("Unable to get member data")?;
-
-    if !has_special_role(member, config).await {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("You don't have permission to use this command!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
     }
 
-    // Check if this channel is managed by the bot
-    if !is_managed_channel(ctx, config, channel_id).await? {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("This channel was not created by the bot and cannot be renamed!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
     }
 
-    let options = &command.data.options();
-    let new_name = if let Some(ResolvedOption {
-        value: ResolvedValue::String(name),
-        ..
-    }) = options.first()
-    {
-        name
-    } else {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("Channel name not valid!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
-    };
-
-    let response = CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content("Renaming channel...")
-            .ephemeral(true),
-    );
-    command.create_response(&ctx.http, response).await?;
-
-    // Rename the channel on Discord
-    command
-        .channel_id
-        .edit(&ctx.http, EditChannel::new().name(*new_name))
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let requested = if let Some(name) = command.string_option("name") {
+            name
+        } else {
+            ctx.respond(&command.interaction, "Channel name not valid!")
+                .await?;
+            return Ok(());
+        };
+
+        let member = command
+            .member
+            .as_ref()
+            .context("Unable to get member data")?;
+        // Managers are not bound by the naming rules
+        let apply_policy = !has_manager_role(member, config).await;
+
+        let kind = ctx.channel(command.channel_id).await?.kind;
+        let new_name = match check_channel_name(
+            ctx,
+            config,
+            registry,
+            requested,
+            kind,
+            apply_policy,
+            Some(channel_id),
+        )
+        .await?
+        {
+            Ok(name) => name,
+            Err(e) => {
+                ctx.respond(&command.interaction, &e.to_string()).await?;
+                return Ok(());
+            }
+        };
+
+        ctx.respond(
+            &command.interaction,
+            &format!(
+                "Renaming channel to {}...",
+                describe_name(requested, &new_name)
+            ),
+        )
         .await?;
 
-    update_channel_list(ctx, config).await?;
-
-    send_action_embed(
-        ctx,
-        config.log_channel_id(),
-        "renamed",
-        command.user.id.get(),
-        EmbedColor::Yellow,
-        Some(command.channel_id),
-    )
-    .await?;
-
-    command
-        .edit_response(
-            &ctx.http,
-            serenity::all::EditInteractionResponse::new().content("Channel renamed successfully!"),
+        // Rename the channel on Discord
+        let channel = ctx
+            .edit_channel(
+                command.channel_id,
+                ChannelEdit {
+                    name: Some(new_name),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        registry
+            .record_renamed(channel_id, &channel.name, command.user_id.get())
+            .await?;
+
+        update_channel_list(ctx, config, registry).await?;
+
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            "renamed",
+            command.user_id.get(),
+            EmbedColor::Yellow,
+            Some(command.channel_id),
         )
         .await?;
 
-    Ok(())
-}
\ No newline at end of file
+        ctx.edit_response(&command.interaction, "Channel renamed successfully!")
+            .await?;
+
+        Ok(())
+    }
+}
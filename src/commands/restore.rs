@@ -0,0 +1,122 @@
+use anyhow::Result;
+use serenity::all::{CommandOptionType, CreateCommandOption};
+use serenity::async_trait;
+
+use super::{CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::naming::check_channel_name;
+use crate::registry::ChannelStatus;
+use crate::utils::{category_full_refusal, update_channel_list};
+
+pub struct Restore;
+
+#[async_trait]
+impl SlashCommand for Restore {
+    fn name(&self) -> &'static str {
+        "restore"
+    }
+
+    fn description(&self) -> &'static str {
+        "Bring a deleted channel back from the trash."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "Channel in the trash",
+        )
+        .required(true)]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::ManagerRole
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+
+        let Some(target) = command.channel_option("channel") else {
+            ctx.respond(&command.interaction, "Channel is not valid!")
+                .await?;
+            return Ok(());
+        };
+
+        let trash = registry
+            .get(target.get())
+            .await
+            .filter(|r| r.status == ChannelStatus::Trashed)
+            .and_then(|r| r.trash);
+        let Some(trash) = trash else {
+            ctx.respond(
+                &command.interaction,
+                &format!("<#{}> is not in the trash!", target),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        if let Some(refusal) = category_full_refusal(ctx, config).await? {
+            ctx.respond(&command.interaction, &refusal).await?;
+            return Ok(());
+        }
+
+        // Another channel may have taken the name in the meantime
+        let channel = ctx.channel(target).await?;
+        if let Err(e) = check_channel_name(
+            ctx,
+            config,
+            registry,
+            &channel.name,
+            channel.kind,
+            false,
+            Some(target.get()),
+        )
+        .await?
+        {
+            ctx.respond(&command.interaction, &e.to_string()).await?;
+            return Ok(());
+        }
+
+        ctx.respond(&command.interaction, "Restoring channel...")
+            .await?;
+
+        ctx.edit_channel(
+            target,
+            ChannelEdit {
+                parent_id: Some(config.category_id()),
+                permission_overwrites: Some(trash.permission_overwrites),
+                ..Default::default()
+            },
+        )
+        .await?;
+        registry.record_restored(target.get()).await?;
+
+        update_channel_list(ctx, config, registry).await?;
+
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            "restored from the trash",
+            command.user_id.get(),
+            EmbedColor::Green,
+            Some(target),
+        )
+        .await?;
+
+        ctx.edit_response(
+            &command.interaction,
+            &format!("Channel restored! <#{}>", target),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::transcript::export_transcript;
+
+pub struct Transcript;
+
+#[async_trait]
+impl SlashCommand for Transcript {
+    fn name(&self) -> &'static str {
+        "transcript"
+    }
+
+    fn description(&self) -> &'static str {
+        "Export the channel's messages and threads."
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Owned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            ..
+        } = cx;
+
+        // Reading a long history takes longer than Discord waits for a reply
+        ctx.respond(&command.interaction, "Exporting transcript...")
+            .await?;
+
+        let transcript = export_transcript(ctx, command.channel_id).await?;
+        ctx.edit_response_with_files(
+            &command.interaction,
+            &transcript.summary(),
+            transcript.files()?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::{CommandOptionType, CreateCommandOption, PermissionOverwriteType};
+use serenity::async_trait;
+
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::ChannelEdit;
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::utils::{
+    has_manager_role, has_special_role, owner_overwrite, record_template, update_channel_list,
+};
+
+pub struct Transfer;
+
+#[async_trait]
+impl SlashCommand for Transfer {
+    fn name(&self) -> &'static str {
+        "transfer"
+    }
+
+    fn description(&self) -> &'static str {
+        "Give the channel to another member."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(CommandOptionType::User, "member", "New owner").required(true),
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "force",
+                "Managers only: transfer even if the member lacks the special role",
+            ),
+        ]
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::PrimaryOwned
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let guild_id = command
+            .guild_id
+            .context("This command can only be used in a server")?;
+        let Some(new_owner) = command.user_option("member") else {
+            ctx.respond(&command.interaction, "Member is not valid!")
+                .await?;
+            return Ok(());
+        };
+
+        let member = command
+            .member
+            .as_ref()
+            .context("Unable to get member data")?;
+        let force = command.bool_option("force").unwrap_or(false);
+        if force && !has_manager_role(member, config).await {
+            ctx.respond(&command.interaction, "Only managers can force a transfer!")
+                .await?;
+            return Ok(());
+        }
+
+        let record = registry.get(channel_id).await;
+        let old_owner = record.as_ref().map(|r| r.owner_id()).unwrap_or(0);
+        if new_owner.get() == old_owner {
+            ctx.respond(
+                &command.interaction,
+                &format!("<@{}> already owns this channel!", new_owner),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let Ok(new_member) = ctx.member(guild_id, new_owner).await else {
+            ctx.respond(
+                &command.interaction,
+                &format!("<@{}> is not a member of this server!", new_owner),
+            )
+            .await?;
+            return Ok(());
+        };
+        if !force && !has_special_role(&new_member, config).await {
+            ctx.respond(
+                &command.interaction,
+                &format!(
+                    "<@{}> does not have the role needed to own channels!",
+                    new_owner
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        ctx.respond(
+            &command.interaction,
+            &format!("Transferring channel to <@{}>...", new_owner),
+        )
+        .await?;
+
+        // Move the owner overwrite, leaving every other overwrite alone
+        let channel = ctx.channel(command.channel_id).await?;
+        let mut overwrites: Vec<_> = channel
+            .permission_overwrites
+            .into_iter()
+            .filter(|o| {
+                !matches!(o.kind, PermissionOverwriteType::Member(user)
+                    if user.get() == old_owner || user == new_owner)
+            })
+            .collect();
+        let template = record.as_ref().and_then(|r| record_template(config, r));
+        overwrites.push(owner_overwrite(new_owner, template));
+        ctx.edit_channel(
+            command.channel_id,
+            ChannelEdit {
+                permission_overwrites: Some(overwrites),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        registry
+            .record_transferred(channel_id, new_owner.get())
+            .await?;
+
+        update_channel_list(ctx, config, registry).await?;
+
+        let from = if old_owner == 0 {
+            "nobody".to_string()
+        } else {
+            format!("<@{}>", old_owner)
+        };
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            &format!("transferred from {} to <@{}>", from, new_owner),
+            command.user_id.get(),
+            EmbedColor::Yellow,
+            Some(command.channel_id),
+        )
+        .await?;
+
+        ctx.edit_response(
+            &command.interaction,
+            &format!("Channel transferred to <@{}>!", new_owner),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context as AnyhowContext, Result};
+use serenity::all::UserId;
+use serenity::async_trait;
+
+use super::archive::ARCHIVE_PREFIX;
+use super::{ChannelRequirement, CommandContext, Permission, SlashCommand};
+use crate::backend::{ChannelEdit, MemberData};
+use crate::embed::{send_action_embed, EmbedColor};
+use crate::naming::check_channel_name;
+use crate::utils::{category_full_refusal, quota_reached, record_overwrites, update_channel_list};
+
+pub struct Unarchive;
+
+#[async_trait]
+impl SlashCommand for Unarchive {
+    fn name(&self) -> &'static str {
+        "unarchive"
+    }
+
+    fn description(&self) -> &'static str {
+        "Move the archived channel back to the channel category."
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::SpecialRole
+    }
+
+    fn channel(&self) -> ChannelRequirement {
+        ChannelRequirement::Archived
+    }
+
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+        let channel_id = command.channel_id.get();
+
+        let guild_id = command
+            .guild_id
+            .context("This command can only be used in a server")?;
+        let record = registry.get(channel_id).await;
+        let owner_id = record.as_ref().map(|r| r.owner_id()).unwrap_or(0);
+
+        if let Some(refusal) = category_full_refusal(ctx, config).await? {
+            ctx.respond(&command.interaction, &refusal).await?;
+            return Ok(());
+        }
+
+        // The restored channel counts against its owner's quota, whoever restores it
+        if owner_id != 0 {
+            // An owner who left the server gets the default limit
+            let owner = ctx
+                .member(guild_id, UserId::new(owner_id))
+                .await
+                .unwrap_or(MemberData { roles: Vec::new() });
+            if let Some(quota) = quota_reached(ctx, config, registry, owner_id, &owner).await? {
+                ctx.respond(
+                    &command.interaction,
+                    &format!(
+                        "<@{}> already owns {} channel(s), the limit is {}. Delete or archive one before unarchiving this channel.",
+                        owner_id,
+                        quota.owned.len(),
+                        quota.limit
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+        }
+
+        let channel = ctx.channel(command.channel_id).await?;
+        let requested = channel
+            .name
+            .strip_prefix(ARCHIVE_PREFIX)
+            .unwrap_or(&channel.name);
+        // The name was accepted once, only make sure it was not taken in the meantime
+        let name = match check_channel_name(
+            ctx,
+            config,
+            registry,
+            requested,
+            channel.kind,
+            false,
+            Some(channel_id),
+        )
+        .await?
+        {
+            Ok(name) => name,
+            Err(e) => {
+                ctx.respond(&command.interaction, &e.to_string()).await?;
+                return Ok(());
+            }
+        };
+
+        ctx.respond(
+            &command.interaction,
+            &format!("Unarchiving channel as #{}...", name),
+        )
+        .await?;
+
+        let mut edit = ChannelEdit {
+            name: Some(name),
+            parent_id: Some(config.category_id()),
+            ..Default::default()
+        };
+        if let Some(record) = &record {
+            // Put back the overwrites from before the archive, or for channels archived
+            // before they were kept, give the owners and contributors theirs back
+            let overwrites = record
+                .archived_overwrites
+                .clone()
+                .unwrap_or(channel.permission_overwrites);
+            edit.permission_overwrites = Some(record_overwrites(overwrites, record, config));
+        }
+
+        let channel = ctx.edit_channel(command.channel_id, edit).await?;
+        registry
+            .record_unarchived(channel_id, &channel.name, command.user_id.get())
+            .await?;
+
+        // Back in the category, the channel gets its entry in the list again
+        update_channel_list(ctx, config, registry).await?;
+
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            "unarchived",
+            command.user_id.get(),
+            EmbedColor::Green,
+            Some(command.channel_id),
+        )
+        .await?;
+
+        ctx.edit_response(&command.interaction, "Channel unarchived successfully!")
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,69 +1,54 @@
-use anyhow::{Context as AnyhowContext, Result};
-use serenity::all::{
-    CommandInteraction, Context, CreateCommand, CreateInteractionResponse,
-    CreateInteractionResponseMessage,
-};
+use anyhow::Result;
+use serenity::async_trait;
 
-use crate::config::Config;
+use super::{CommandContext, Permission, SlashCommand};
 use crate::embed::{send_action_embed, EmbedColor};
-use crate::utils::{has_manager_role, update_channel_list};
+use crate::utils::rebuild_channel_list;
 
-pub fn register() -> CreateCommand {
-    let name = "updatelist";
-    let description = "Force refresh the channel list.";
+pub struct UpdateList;
 
-    println!(" > /{} - {}", name, description);
-
-    CreateCommand::new(name).description(description)
-}
-
-pub async fn run(
-    ctx: &Context,
-    command: &CommandInteraction,
-    config: &Config,
-) -> Result<()> {
-    let member = command
-        .member
-        .as_ref()
-        .context("Unable to get member data")?;
-
-    if !has_manager_role(member, config).await {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content("Only managers can use this command!")
-                .ephemeral(true),
-        );
-        command.create_response(&ctx.http, response).await?;
-        return Ok(());
+#[async_trait]
+impl SlashCommand for UpdateList {
+    fn name(&self) -> &'static str {
+        "updatelist"
     }
 
-    let response = CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content("Rebuilding the list...")
-            .ephemeral(true),
-    );
-    command.create_response(&ctx.http, response).await?;
-
-    // Simply rebuild the list from current Discord channels
-    update_channel_list(ctx, config).await?;
+    fn description(&self) -> &'static str {
+        "Force refresh the channel list."
+    }
 
-    send_action_embed(
-        ctx,
-        config.log_channel_id(),
-        "updated",
-        command.user.id.get(),
-        EmbedColor::Blue,
-        Some(config.list_channel_id()),
-    )
-    .await?;
+    fn permission(&self) -> Permission {
+        Permission::ManagerRole
+    }
 
-    command
-        .edit_response(
-            &ctx.http,
-            serenity::all::EditInteractionResponse::new()
-                .content("Channel list successfully rebuilt!"),
+    async fn run(&self, cx: CommandContext<'_>) -> Result<()> {
+        let CommandContext {
+            backend: ctx,
+            request: command,
+            config,
+            registry,
+            ..
+        } = cx;
+
+        ctx.respond(&command.interaction, "Rebuilding the list...")
+            .await?;
+
+        // Simply rebuild the list from current Discord channels
+        rebuild_channel_list(ctx, config, registry).await?;
+
+        send_action_embed(
+            ctx,
+            config.log_channel_id(),
+            "updated",
+            command.user_id.get(),
+            EmbedColor::Blue,
+            Some(config.list_channel_id()),
         )
         .await?;
 
-    Ok(())
-}
\ No newline at end of file
+        ctx.edit_response(&command.interaction, "Channel list successfully rebuilt!")
+            .await?;
+
+        Ok(())
+    }
+}
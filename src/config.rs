@@ -1,25 +1,708 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use serenity::model::id::{ChannelId, RoleId};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::model::Permissions;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// How the channel list is rendered in the list channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListStyle {
+    /// One message per channel
+    #[default]
+    PerChannel,
+    /// Many channels per embed, grouped under alphabetical headers
+    Compact,
+}
+
+/// How private channels appear in the channel list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivateListing {
+    /// Listed with a lock in front of the entry
+    #[default]
+    Locked,
+    /// Left out of the list
+    Hidden,
+}
+
+/// Where slash commands are registered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandScope {
+    /// Once for the whole application, can take up to an hour to show up
+    #[default]
+    Global,
+    /// In each configured server, available immediately
+    Guild,
+}
+
+impl CommandScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandScope::Global => "global",
+            CommandScope::Guild => "guild",
+        }
+    }
+}
+
+/// Days a deleted channel stays in the trash when `trash_retention_days` is not set
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 7;
+
+/// Prefix of every environment variable read by the bot
+pub const ENV_PREFIX: &str = "CHANNEL_BOT_";
+
+/// Per-server settings that can be overridden from the environment, and whether they are numbers
+const GUILD_SETTINGS: [(&str, bool); 11] = [
+    ("category_id", true),
+    ("log_channel_id", true),
+    ("list_channel_id", true),
+    ("special_role_id", true),
+    ("manager_role_id", true),
+    ("list_style", false),
+    ("max_channels_per_user", true),
+    ("archive_category_id", true),
+    ("trash_category_id", true),
+    ("trash_retention_days", true),
+    ("private_listing", false),
+];
+
+/// Per-server settings without a default value
+const REQUIRED_GUILD_SETTINGS: [&str; 5] = [
+    "category_id",
+    "log_channel_id",
+    "list_channel_id",
+    "special_role_id",
+    "manager_role_id",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub token: String,
+    /// File holding the token, used instead of `token` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+    /// File where the channel registry is persisted
+    #[serde(default = "default_registry_path")]
+    pub registry_path: String,
+    /// Where slash commands are registered
+    #[serde(default)]
+    pub command_scope: CommandScope,
+    /// Settings of each server the bot manages, keyed by guild id
+    #[serde(default)]
+    pub guilds: BTreeMap<u64, GuildConfig>,
+}
+
+/// Settings of a single server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuildConfig {
+    /// Filled from the table key when loading
+    #[serde(skip)]
+    pub guild_id: u64,
     pub category_id: u64,
     pub log_channel_id: u64,
     pub list_channel_id: u64,
     pub special_role_id: u64,
     pub manager_role_id: u64,
+    #[serde(default)]
+    pub list_style: ListStyle,
+    /// Whether private channels are listed locked or left out of the list
+    #[serde(default)]
+    pub private_listing: PrivateListing,
+    /// Managed channels a member may own, unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_channels_per_user: Option<u32>,
+    /// Limits replacing `max_channels_per_user` for members with a role
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub role_channel_limits: Vec<RoleChannelLimit>,
+    /// Rules channel names must follow, managers are exempt
+    #[serde(default, skip_serializing_if = "NamingPolicy::is_empty")]
+    pub naming: NamingPolicy,
+    /// Category archived channels are moved to, archiving is disabled when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_category_id: Option<u64>,
+    /// Make /delete archive the channel unless `permanent` is set
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archive_on_delete: bool,
+    /// Hidden category deleted channels wait in before being purged, they are deleted
+    /// right away when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_category_id: Option<u64>,
+    /// Days a channel stays in the trash, 7 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_retention_days: Option<u32>,
+    /// Overwrites /create can apply instead of the default ones, keyed by template name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, PermissionTemplate>,
+}
+
+/// Overwrites given to a new channel, targets left unset keep the default overwrite
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PermissionTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<PermissionRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub everyone: Option<PermissionRule>,
+    /// Extra overwrites for specific roles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<RolePermissionRule>,
+}
+
+/// Permissions allowed and denied by an overwrite, by name such as `send_messages`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRule {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// Overwrite of a template for the members of a role
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RolePermissionRule {
+    pub role_id: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// Permission called `name`, case-insensitive, as in the Discord API (`send_messages`)
+pub fn parse_permission(name: &str) -> Option<Permissions> {
+    Permissions::from_name(&name.trim().to_uppercase())
+}
+
+/// Combine permissions validated at load time, ignoring names that are not recognised
+fn parse_permissions(names: &[String]) -> Permissions {
+    names
+        .iter()
+        .filter_map(|name| parse_permission(name))
+        .fold(Permissions::empty(), |acc, p| acc | p)
+}
+
+impl PermissionRule {
+    pub fn allow(&self) -> Permissions {
+        parse_permissions(&self.allow)
+    }
+
+    pub fn deny(&self) -> Permissions {
+        parse_permissions(&self.deny)
+    }
+}
+
+impl RolePermissionRule {
+    pub fn role_id(&self) -> RoleId {
+        RoleId::new(self.role_id)
+    }
+
+    pub fn allow(&self) -> Permissions {
+        parse_permissions(&self.allow)
+    }
+
+    pub fn deny(&self) -> Permissions {
+        parse_permissions(&self.deny)
+    }
+}
+
+impl PermissionTemplate {
+    /// Every permission the template allows or denies, which the bot must hold to set
+    pub fn permissions(&self) -> Permissions {
+        let rules = self.owner.iter().chain(&self.everyone);
+        let roles = self.roles.iter().map(|r| r.allow() | r.deny());
+        rules
+            .map(|r| r.allow() | r.deny())
+            .chain(roles)
+            .fold(Permissions::empty(), |acc, p| acc | p)
+    }
+}
+
+/// Rules applied to normalized channel names
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamingPolicy {
+    /// Every name must start with this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_prefix: Option<String>,
+    /// Regular expression every name must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Names that cannot be used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reserved_names: Vec<String>,
+    /// Words that cannot appear anywhere in a name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_words: Vec<String>,
+}
+
+impl NamingPolicy {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Channel limit for the members of a role
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleChannelLimit {
+    pub role_id: u64,
+    pub max_channels: u32,
+}
+
+fn default_registry_path() -> String {
+    "channels.json".to_string()
+}
+
+/// A difference between two configurations
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    /// Server the change applies to, `None` for bot-wide settings
+    pub guild_id: Option<u64>,
+    pub description: String,
 }
 
 impl Config {
+    /// Load the configuration from `path`, the `CHANNEL_BOT_*` environment variables and
+    /// the token file. The file may be missing when the environment provides everything.
     pub fn load(path: &str) -> Result<Self> {
-        let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let content = match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Unable to read {}", path)),
+        };
+        let env: BTreeMap<String, String> = std::env::vars()
+            .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+            .collect();
+
+        let config = Self::from_sources(path, content.as_deref(), &env)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Merge the config file content with environment overrides.
+    ///
+    /// Precedence, highest first:
+    /// - token: `CHANNEL_BOT_TOKEN`, the file named by `CHANNEL_BOT_TOKEN_FILE`, the file
+    ///   named by `token_file`, then `token`
+    /// - server settings: `CHANNEL_BOT_GUILD_<guild id>_<SETTING>`, then
+    ///   `CHANNEL_BOT_<SETTING>` for the server named by `CHANNEL_BOT_GUILD_ID` (or the only
+    ///   configured server), then the `[guilds.<guild id>]` table
+    /// - `registry_path` and `command_scope`: `CHANNEL_BOT_REGISTRY_PATH` and
+    ///   `CHANNEL_BOT_COMMAND_SCOPE`, then the file
+    pub fn from_sources(
+        path: &str,
+        content: Option<&str>,
+        env: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let mut table: toml::Table = match content {
+            Some(content) => {
+                toml::from_str(content).with_context(|| format!("Invalid {}", path))?
+            }
+            None => toml::Table::new(),
+        };
+
+        for key in ["registry_path", "command_scope"] {
+            if let Some(value) = env.get(&env_name(key)) {
+                table.insert(key.to_string(), toml::Value::String(value.clone()));
+            }
+        }
+        apply_guild_env(&mut table, env)?;
+        resolve_token(&mut table, env)?;
+        check_required(&table, path)?;
+
+        // Round-trip through text so server table keys are parsed as ids
+        Self::parse(&toml::to_string(&table)?).with_context(|| format!("Invalid {}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(content)?;
+        for (guild_id, guild) in config.guilds.iter_mut() {
+            guild.guild_id = *guild_id;
+        }
         Ok(config)
     }
 
+    /// Settings of the server a command was used in
+    pub fn guild(&self, guild_id: GuildId) -> Option<&GuildConfig> {
+        self.guilds.get(&guild_id.get())
+    }
+
+    /// Check the settings that can be verified without talking to Discord
+    pub fn validate(&self) -> Result<()> {
+        if self.token.trim().is_empty() {
+            bail!("token is empty");
+        }
+        if self.guilds.is_empty() {
+            bail!(
+                "no server configured: add a [guilds.<id>] table or set {}",
+                env_name("GUILD_ID")
+            );
+        }
+
+        for (guild_id, guild) in &self.guilds {
+            if *guild_id == 0 {
+                bail!("guild id 0 is not valid");
+            }
+            for (name, id) in [
+                ("category_id", guild.category_id),
+                ("log_channel_id", guild.log_channel_id),
+                ("list_channel_id", guild.list_channel_id),
+                ("special_role_id", guild.special_role_id),
+                ("manager_role_id", guild.manager_role_id),
+            ] {
+                if id == 0 {
+                    bail!("guild {}: {} is not set", guild_id, name);
+                }
+            }
+            if guild.log_channel_id == guild.list_channel_id {
+                bail!("guild {}: log and list channel must be different", guild_id);
+            }
+            if guild.role_channel_limits.iter().any(|l| l.role_id == 0) {
+                bail!("guild {}: role_channel_limits role_id is not set", guild_id);
+            }
+            match guild.archive_category_id {
+                Some(0) => bail!("guild {}: archive_category_id is not set", guild_id),
+                Some(id) if id == guild.category_id => bail!(
+                    "guild {}: archive category must be different from the category",
+                    guild_id
+                ),
+                None if guild.archive_on_delete => bail!(
+                    "guild {}: archive_on_delete requires archive_category_id",
+                    guild_id
+                ),
+                _ => {}
+            }
+            match guild.trash_category_id {
+                Some(0) => bail!("guild {}: trash_category_id is not set", guild_id),
+                Some(id) if id == guild.category_id || guild.archive_category_id == Some(id) => {
+                    bail!(
+                        "guild {}: trash category must be different from the other categories",
+                        guild_id
+                    )
+                }
+                _ => {}
+            }
+            if guild.trash_retention_days == Some(0) {
+                bail!(
+                    "guild {}: trash_retention_days must be at least 1",
+                    guild_id
+                );
+            }
+            for (name, template) in &guild.templates {
+                validate_template(name, template)
+                    .map_err(|e| anyhow!("guild {}: template `{}`: {}", guild_id, name, e))?;
+            }
+            if let Some(prefix) = &guild.naming.required_prefix {
+                if crate::naming::normalize_prefix(prefix).is_empty() {
+                    bail!(
+                        "guild {}: naming.required_prefix `{}` has no letters or digits",
+                        guild_id,
+                        prefix
+                    );
+                }
+            }
+            if let Some(pattern) = &guild.naming.pattern {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    bail!("guild {}: naming.pattern is not valid: {}", guild_id, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Describe every setting that differs in `new`
+    pub fn diff(&self, new: &Config) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+
+        if self.token != new.token {
+            changes.push(ConfigChange {
+                guild_id: None,
+                description: "token changed (restart required)".to_string(),
+            });
+        }
+        if self.registry_path != new.registry_path {
+            changes.push(ConfigChange {
+                guild_id: None,
+                description: format!(
+                    "registry_path: {} -> {} (restart required)",
+                    self.registry_path, new.registry_path
+                ),
+            });
+        }
+        if self.command_scope != new.command_scope {
+            changes.push(ConfigChange {
+                guild_id: None,
+                description: format!(
+                    "command_scope: {} -> {} (restart required)",
+                    self.command_scope.as_str(),
+                    new.command_scope.as_str()
+                ),
+            });
+        }
+
+        let guild_ids: BTreeSet<u64> = self
+            .guilds
+            .keys()
+            .chain(new.guilds.keys())
+            .copied()
+            .collect();
+        for guild_id in guild_ids {
+            let description = match (self.guilds.get(&guild_id), new.guilds.get(&guild_id)) {
+                (Some(_), None) => vec!["server removed".to_string()],
+                (None, Some(_)) => vec!["server added".to_string()],
+                (Some(old), Some(new)) => diff_settings(old, new),
+                (None, None) => Vec::new(),
+            };
+            changes.extend(description.into_iter().map(|description| ConfigChange {
+                guild_id: Some(guild_id),
+                description,
+            }));
+        }
+
+        changes
+    }
+}
+
+/// Check the permission names and role ids of a template
+fn validate_template(name: &str, template: &PermissionTemplate) -> Result<()> {
+    if name.trim().is_empty() {
+        bail!("the name is empty");
+    }
+
+    let mut rules: Vec<(String, &[String])> = Vec::new();
+    for (target, rule) in [("owner", &template.owner), ("everyone", &template.everyone)] {
+        if let Some(rule) = rule {
+            rules.push((format!("{}.allow", target), &rule.allow));
+            rules.push((format!("{}.deny", target), &rule.deny));
+        }
+    }
+    for rule in &template.roles {
+        if rule.role_id == 0 {
+            bail!("roles role_id is not set");
+        }
+        rules.push((format!("roles.{}.allow", rule.role_id), &rule.allow));
+        rules.push((format!("roles.{}.deny", rule.role_id), &rule.deny));
+    }
+
+    for (target, names) in rules {
+        if let Some(unknown) = names.iter().find(|n| parse_permission(n).is_none()) {
+            bail!("{}: `{}` is not a Discord permission", target, unknown);
+        }
+    }
+
+    Ok(())
+}
+
+fn env_name(setting: &str) -> String {
+    format!("{}{}", ENV_PREFIX, setting.to_uppercase())
+}
+
+/// Apply `CHANNEL_BOT_<SETTING>` and `CHANNEL_BOT_GUILD_<id>_<SETTING>` overrides
+fn apply_guild_env(table: &mut toml::Table, env: &BTreeMap<String, String>) -> Result<()> {
+    let guilds = table
+        .entry("guilds")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .context("`guilds` must be a table")?;
+
+    // Server the unqualified variables apply to
+    let default_guild = match env.get(&env_name("GUILD_ID")) {
+        Some(id) => Some(id.trim().to_string()),
+        None if guilds.len() == 1 => guilds.keys().next().cloned(),
+        None => None,
+    };
+
+    let mut overrides: Vec<(String, &str, String, &String)> = Vec::new();
+    for (setting, _) in GUILD_SETTINGS {
+        let name = env_name(setting);
+        if let Some(value) = env.get(&name) {
+            let guild_id = default_guild.clone().ok_or_else(|| {
+                anyhow!(
+                    "{} is set but there are several servers: set {} to choose one",
+                    name,
+                    env_name("GUILD_ID")
+                )
+            })?;
+            overrides.push((guild_id, setting, name, value));
+        }
+    }
+    let guild_prefix = env_name("GUILD_");
+    for (name, value) in env {
+        let Some(rest) = name.strip_prefix(&guild_prefix) else {
+            continue;
+        };
+        let Some((guild_id, setting)) = rest.split_once('_') else {
+            continue;
+        };
+        let setting = setting.to_lowercase();
+        if let Some((setting, _)) = GUILD_SETTINGS.iter().find(|(s, _)| *s == setting) {
+            overrides.push((guild_id.to_string(), setting, name.clone(), value));
+        }
+    }
+
+    for (guild_id, setting, name, value) in overrides {
+        if guild_id.parse::<u64>().is_err() {
+            bail!("{}: `{}` is not a valid server id", name, guild_id);
+        }
+        let is_number = GUILD_SETTINGS
+            .iter()
+            .any(|(s, number)| *s == setting && *number);
+        let value = if is_number {
+            let number = value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| anyhow!("{} must be a number, got `{}`", name, value))?;
+            toml::Value::Integer(number)
+        } else {
+            toml::Value::String(value.trim().to_string())
+        };
+
+        guilds
+            .entry(guild_id)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .context("Each `guilds` entry must be a table")?
+            .insert(setting.to_string(), value);
+    }
+
+    Ok(())
+}
+
+/// Fill `token` from the environment or a token file, following the precedence rules
+fn resolve_token(table: &mut toml::Table, env: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(token) = env.get(&env_name("TOKEN")) {
+        table.insert(
+            "token".to_string(),
+            toml::Value::String(token.trim().to_string()),
+        );
+        return Ok(());
+    }
+
+    let token_file = env.get(&env_name("TOKEN_FILE")).cloned().or_else(|| {
+        table
+            .get("token_file")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    });
+    if let Some(token_file) = token_file {
+        let token = fs::read_to_string(&token_file)
+            .with_context(|| format!("Unable to read token file {}", token_file))?;
+        table.insert(
+            "token".to_string(),
+            toml::Value::String(token.trim().to_string()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Fail with the name of the first missing setting and where it can be provided
+fn check_required(table: &toml::Table, path: &str) -> Result<()> {
+    if !table.contains_key("token") {
+        bail!(
+            "Missing setting `token`: set {}, {}, or `token`/`token_file` in {}",
+            env_name("TOKEN"),
+            env_name("TOKEN_FILE"),
+            path
+        );
+    }
+
+    if let Some(guilds) = table.get("guilds").and_then(|g| g.as_table()) {
+        for (guild_id, guild) in guilds {
+            for setting in REQUIRED_GUILD_SETTINGS {
+                if guild.get(setting).is_none() {
+                    bail!(
+                        "Missing setting `{}` for server {}: set {} or {}, or add it to [guilds.{}] in {}",
+                        setting,
+                        guild_id,
+                        env_name(&format!("GUILD_{}_{}", guild_id, setting)),
+                        env_name(setting),
+                        guild_id,
+                        path
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Compare two settings tables key by key
+fn diff_settings<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+    let (Ok(toml::Value::Table(old)), Ok(toml::Value::Table(new))) =
+        (toml::Value::try_from(old), toml::Value::try_from(new))
+    else {
+        return Vec::new();
+    };
+
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = old.get(key).map(ToString::to_string);
+            let new = new.get(key).map(ToString::to_string);
+            (old != new).then(|| {
+                format!(
+                    "{}: {} -> {}",
+                    key,
+                    old.as_deref().unwrap_or("(unset)"),
+                    new.as_deref().unwrap_or("(unset)")
+                )
+            })
+        })
+        .collect()
+}
+
+/// Configuration shared by every interaction, which can be replaced while the bot runs
+pub struct SharedConfig {
+    path: String,
+    current: RwLock<Arc<Config>>,
+    /// Number of reloads attempted, so cached checks know when to run again
+    generation: AtomicU64,
+}
+
+impl SharedConfig {
+    pub fn new(path: &str, config: Config) -> Self {
+        Self {
+            path: path.to_string(),
+            current: RwLock::new(Arc::new(config)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Configuration to use for the interaction being handled
+    pub fn current(&self) -> Arc<Config> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Bumped on every reload, even when nothing changed or the file was invalid
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Load the file again and swap it in if it is valid, keeping the current
+    /// configuration otherwise. Returns the previous configuration and the changes.
+    pub fn reload(&self) -> Result<(Arc<Config>, Vec<ConfigChange>)> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let new = Config::load(&self.path)?;
+
+        let mut current = self.current.write().unwrap();
+        let changes = current.diff(&new);
+        let old = std::mem::replace(&mut *current, Arc::new(new));
+        Ok((old, changes))
+    }
+}
+
+impl GuildConfig {
+    pub fn guild_id(&self) -> GuildId {
+        GuildId::new(self.guild_id)
+    }
+
     pub fn category_id(&self) -> ChannelId {
         ChannelId::new(self.category_id)
     }
@@ -39,4 +722,32 @@ impl Config {
     pub fn manager_role_id(&self) -> RoleId {
         RoleId::new(self.manager_role_id)
     }
-}
\ No newline at end of file
+
+    pub fn archive_category_id(&self) -> Option<ChannelId> {
+        self.archive_category_id.map(ChannelId::new)
+    }
+
+    pub fn trash_category_id(&self) -> Option<ChannelId> {
+        self.trash_category_id.map(ChannelId::new)
+    }
+
+    pub fn trash_retention_days(&self) -> u32 {
+        self.trash_retention_days
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+    }
+
+    pub fn template(&self, name: &str) -> Option<&PermissionTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Channels a member with `roles` may own: the highest limit among their roles,
+    /// otherwise `max_channels_per_user`. `None` means unlimited.
+    pub fn channel_limit(&self, roles: &[RoleId]) -> Option<u32> {
+        self.role_channel_limits
+            .iter()
+            .filter(|l| roles.contains(&RoleId::new(l.role_id)))
+            .map(|l| l.max_channels)
+            .max()
+            .or(self.max_channels_per_user)
+    }
+}
@@ -1,25 +1,26 @@
 use anyhow::Result;
-use serenity::all::{ChannelId, Context, CreateEmbed, CreateMessage, Timestamp};
+use serenity::all::{ChannelId, MessageId};
+
+use crate::backend::{Backend, EmbedData};
+use crate::utils::ChannelInfo;
 
 pub async fn send_log_embed(
-    ctx: &Context,
+    ctx: &dyn Backend,
     channel_id: ChannelId,
     description: &str,
     color: Option<u32>,
 ) -> Result<()> {
-    let embed = CreateEmbed::new()
-        .description(description)
-        .color(color.unwrap_or(5814783))
-        .timestamp(Timestamp::now());
-
-    let message = CreateMessage::new().embed(embed);
+    let embed = EmbedData {
+        description: description.to_string(),
+        color: color.unwrap_or(5814783),
+    };
 
-    channel_id.send_message(&ctx.http, message).await?;
+    ctx.send_embeds(channel_id, vec![embed]).await?;
     Ok(())
 }
 
 pub async fn send_action_embed(
-    ctx: &Context,
+    ctx: &dyn Backend,
     channel_id: ChannelId,
     action: &str,
     user_id: u64,
@@ -35,32 +36,191 @@ pub async fn send_action_embed(
     send_log_embed(ctx, channel_id, &description, Some(color.value())).await
 }
 
-/// Send an embed for the channel list
-/// Format: [#Channel] - description by @User
-pub async fn send_list_embed(
-    ctx: &Context,
-    list_channel_id: ChannelId,
+// Discord limits for embeds in a single message
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+const EMBEDS_PER_MESSAGE: usize = 10;
+const MESSAGE_EMBED_TEXT_LIMIT: usize = 6000;
+
+/// Content of one message in the list channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListPage {
+    /// Channels rendered by this message
+    pub channel_ids: Vec<u64>,
+    /// Description of each embed in the message
+    pub embeds: Vec<String>,
+}
+
+/// Marks the list entries of private channels
+pub const PRIVATE_MARKER: &str = "🔒";
+
+/// Text of a channel list entry
+/// Format: [🔒] [#Channel] - description by @User with @CoOwner, @CoOwner
+pub fn list_entry_text(
     channel_id: ChannelId,
     description: Option<&String>,
-    creator_id: u64,
-) -> Result<()> {
+    owner_id: u64,
+    co_owner_ids: &[u64],
+    private: bool,
+) -> String {
     let desc_text = if let Some(desc) = description {
         format!(" - {}", desc)
     } else {
         String::new()
     };
 
-    let embed_description = format!("<#{}>{} by <@{}>", channel_id, desc_text, creator_id);
+    let co_owners_text = if co_owner_ids.is_empty() {
+        String::new()
+    } else {
+        let mentions: Vec<String> = co_owner_ids.iter().map(|id| format!("<@{}>", id)).collect();
+        format!(" with {}", mentions.join(", "))
+    };
 
-    let embed = CreateEmbed::new()
-        .description(embed_description)
-        .color(5814783) // Blue color
-        .timestamp(Timestamp::now());
+    let marker = if private {
+        format!("{} ", PRIVATE_MARKER)
+    } else {
+        String::new()
+    };
 
-    let message = CreateMessage::new().embed(embed);
+    format!(
+        "{}<#{}>{} by <@{}>{}",
+        marker, channel_id, desc_text, owner_id, co_owners_text
+    )
+}
 
-    list_channel_id.send_message(&ctx.http, message).await?;
-    Ok(())
+/// Render the channel list with one message per channel
+pub fn per_channel_pages(channels: &[ChannelInfo]) -> Vec<ListPage> {
+    channels
+        .iter()
+        .map(|c| ListPage {
+            channel_ids: vec![c.channel_id],
+            embeds: vec![list_entry_text(
+                ChannelId::new(c.channel_id),
+                c.description.as_ref(),
+                c.owner_id,
+                &c.co_owner_ids,
+                c.private,
+            )],
+        })
+        .collect()
+}
+
+/// Section header a channel is listed under, based on the first character of its name
+fn section_of(name: &str) -> String {
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => c.to_uppercase().collect(),
+        _ => "#".to_string(),
+    }
+}
+
+/// Render the channel list packing as many entries as fit into each embed and as many
+/// embeds as fit into each message, grouped under alphabetical section headers.
+/// `channels` must already be sorted by name.
+pub fn compact_pages(channels: &[ChannelInfo]) -> Vec<ListPage> {
+    let mut pages = Vec::new();
+    let mut page = ListPage {
+        channel_ids: Vec::new(),
+        embeds: Vec::new(),
+    };
+    let mut page_len = 0;
+    let mut embed = String::new();
+    let mut section: Option<String> = None;
+
+    for channel in channels {
+        let entry = list_entry_text(
+            ChannelId::new(channel.channel_id),
+            channel.description.as_ref(),
+            channel.owner_id,
+            &channel.co_owner_ids,
+            channel.private,
+        );
+        let channel_section = section_of(&channel.name);
+        let starts_section = section.as_deref() != Some(channel_section.as_str());
+
+        let addition = if embed.is_empty() {
+            format!("**{}**\n{}", channel_section, entry)
+        } else if starts_section {
+            format!("\n\n**{}**\n{}", channel_section, entry)
+        } else {
+            format!("\n{}", entry)
+        };
+        let embed_len = embed.chars().count();
+        let addition_len = addition.chars().count();
+
+        if embed_len + addition_len > EMBED_DESCRIPTION_LIMIT
+            || page_len + embed_len + addition_len > MESSAGE_EMBED_TEXT_LIMIT
+        {
+            if !embed.is_empty() {
+                page_len += embed_len;
+                page.embeds.push(std::mem::take(&mut embed));
+            }
+
+            // Repeat the header when a section continues in a new embed
+            let text = if starts_section {
+                format!("**{}**\n{}", channel_section, entry)
+            } else {
+                format!("**{}** (cont.)\n{}", channel_section, entry)
+            };
+
+            if page.embeds.len() == EMBEDS_PER_MESSAGE
+                || page_len + text.chars().count() > MESSAGE_EMBED_TEXT_LIMIT
+            {
+                pages.push(std::mem::replace(
+                    &mut page,
+                    ListPage {
+                        channel_ids: Vec::new(),
+                        embeds: Vec::new(),
+                    },
+                ));
+                page_len = 0;
+            }
+
+            embed = text;
+        } else {
+            embed.push_str(&addition);
+        }
+
+        page.channel_ids.push(channel.channel_id);
+        section = Some(channel_section);
+    }
+
+    if !embed.is_empty() {
+        page.embeds.push(embed);
+    }
+    if !page.embeds.is_empty() {
+        pages.push(page);
+    }
+
+    pages
+}
+
+fn list_embeds(embeds: &[String]) -> Vec<EmbedData> {
+    embeds
+        .iter()
+        .map(|description| EmbedData {
+            description: description.clone(),
+            color: 5814783, // Blue color
+        })
+        .collect()
+}
+
+/// Send a message of the channel list
+pub async fn send_list_message(
+    ctx: &dyn Backend,
+    list_channel_id: ChannelId,
+    embeds: &[String],
+) -> Result<MessageId> {
+    ctx.send_embeds(list_channel_id, list_embeds(embeds)).await
+}
+
+/// Replace the embeds of an existing channel list message
+pub async fn edit_list_message(
+    ctx: &dyn Backend,
+    list_channel_id: ChannelId,
+    message_id: MessageId,
+    embeds: &[String],
+) -> Result<()> {
+    ctx.edit_embeds(list_channel_id, message_id, list_embeds(embeds))
+        .await
 }
 
 pub enum EmbedColor {
@@ -79,4 +239,4 @@ impl EmbedColor {
             EmbedColor::Yellow => 16776960,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,401 @@
+use serenity::all::{ChannelId, ChannelType, Permissions, RoleId, UserId};
+use std::collections::BTreeSet;
+
+use crate::backend::{Backend, ChannelData, RoleData};
+use crate::config::GuildConfig;
+use crate::utils::channel_permissions;
+
+/// Commands that read the category and write to the list and log channels
+const CHANNEL_COMMANDS: &[&str] = &[
+    "create",
+    "delete",
+    "rename",
+    "description",
+    "archive",
+    "unarchive",
+    "restore",
+    "transcript",
+    "transfer",
+    "owners",
+    "contributors",
+    "invite",
+    "kick",
+    "updatelist",
+];
+/// Commands restricted to the special role
+const SPECIAL_COMMANDS: &[&str] = &[
+    "create",
+    "delete",
+    "rename",
+    "description",
+    "archive",
+    "unarchive",
+    "transcript",
+    "transfer",
+    "owners",
+    "contributors",
+    "invite",
+    "kick",
+];
+/// Commands restricted to the manager role
+const MANAGER_COMMANDS: &[&str] = &["updatelist", "reloadconfig", "restore"];
+/// Commands that edit or delete managed channels
+const EDIT_COMMANDS: &[&str] = &[
+    "create",
+    "delete",
+    "rename",
+    "description",
+    "archive",
+    "unarchive",
+    "restore",
+    "transfer",
+    "owners",
+    "contributors",
+    "invite",
+    "kick",
+];
+/// Commands that set the permissions of owners, contributors or invited members on a channel in the category
+const OVERWRITE_COMMANDS: &[&str] = &[
+    "create",
+    "unarchive",
+    "restore",
+    "transfer",
+    "owners",
+    "contributors",
+    "invite",
+    "kick",
+];
+/// Commands that apply permission templates
+const TEMPLATE_COMMANDS: &[&str] = &["create"];
+/// Commands that move channels to the archive category, depending on `archive_on_delete`
+const ARCHIVE_COMMANDS: &[&str] = &["archive"];
+const ARCHIVE_AND_DELETE_COMMANDS: &[&str] = &["archive", "delete"];
+/// Commands that move channels to or from the trash category
+const TRASH_COMMANDS: &[&str] = &["delete", "restore"];
+
+/// Outcome of a single startup check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub description: String,
+    /// Why the check failed, `None` when it passed
+    pub problem: Option<String>,
+    /// Commands that cannot work while this check fails
+    pub disables: &'static [&'static str],
+}
+
+/// Result of checking a server's configuration against the live guild
+#[derive(Debug, Clone)]
+pub struct GuildHealth {
+    /// Configuration the checks were run against
+    pub config: GuildConfig,
+    pub checks: Vec<Check>,
+}
+
+impl GuildHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.problem.is_none())
+    }
+
+    /// Commands disabled by a failed check
+    pub fn disabled_commands(&self) -> BTreeSet<&'static str> {
+        self.failed()
+            .flat_map(|c| c.disables.iter().copied())
+            .collect()
+    }
+
+    /// Why `command` cannot run in this server, if a failed check disables it
+    pub fn disabled_reason(&self, command: &str) -> Option<String> {
+        let reasons: Vec<String> = self
+            .failed()
+            .filter(|c| c.disables.contains(&command))
+            .map(|c| format!("{}: {}", c.description, c.problem.as_deref().unwrap_or("")))
+            .collect();
+        if reasons.is_empty() {
+            None
+        } else {
+            Some(reasons.join("; "))
+        }
+    }
+
+    /// Human readable report of every check, one per line
+    pub fn report(&self) -> String {
+        let mut lines = vec![format!(
+            "Configuration check for server {}:",
+            self.config.guild_id
+        )];
+        for check in &self.checks {
+            lines.push(match &check.problem {
+                None => format!("  [ok]   {}", check.description),
+                Some(problem) => format!("  [FAIL] {}: {}", check.description, problem),
+            });
+        }
+
+        let disabled = self.disabled_commands();
+        if disabled.is_empty() {
+            lines.push("  All commands enabled".to_string());
+        } else {
+            let names: Vec<String> = disabled.iter().map(|c| format!("/{}", c)).collect();
+            lines.push(format!("  Disabled commands: {}", names.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn failed(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.problem.is_some())
+    }
+}
+
+fn check(description: String, problem: Option<String>, disables: &'static [&'static str]) -> Check {
+    Check {
+        description,
+        problem,
+        disables,
+    }
+}
+
+/// Find a configured channel and make sure it has the expected type
+fn check_channel<'a>(
+    channels: &'a [ChannelData],
+    label: &str,
+    channel_id: ChannelId,
+    kind: ChannelType,
+) -> (Check, Option<&'a ChannelData>) {
+    let expected = if kind == ChannelType::Category {
+        "a category"
+    } else {
+        "a text channel"
+    };
+    let description = format!("{} {} is {}", label, channel_id, expected);
+
+    let channel = channels.iter().find(|c| c.id == channel_id);
+    let problem = match channel {
+        None => Some("not found in this server".to_string()),
+        Some(c) if c.kind != kind => Some(format!("it is a {:?} channel", c.kind)),
+        Some(_) => None,
+    };
+
+    let found = channel.filter(|_| problem.is_none());
+    (check(description, problem, CHANNEL_COMMANDS), found)
+}
+
+fn check_role(
+    roles: &[RoleData],
+    label: &str,
+    role_id: RoleId,
+    disables: &'static [&'static str],
+) -> Check {
+    let problem = if roles.iter().any(|r| r.id == role_id) {
+        None
+    } else {
+        Some("not found in this server".to_string())
+    };
+    check(format!("{} {} exists", label, role_id), problem, disables)
+}
+
+/// Make sure the bot holds `needed` in `channel`
+fn check_permissions(
+    description: String,
+    channel: Option<&ChannelData>,
+    bot: Option<(&[RoleData], UserId, &[RoleId])>,
+    needed: Permissions,
+    disables: &'static [&'static str],
+) -> Check {
+    let problem = match (channel, bot) {
+        (None, _) => Some("the channel is unavailable".to_string()),
+        (_, None) => Some("the bot's roles could not be read".to_string()),
+        (Some(channel), Some((roles, user_id, member_roles))) => {
+            let missing = needed - channel_permissions(channel, roles, user_id, member_roles);
+            if missing.is_empty() {
+                None
+            } else {
+                Some(format!(
+                    "missing {}",
+                    missing.get_permission_names().join(", ")
+                ))
+            }
+        }
+    };
+    check(description, problem, disables)
+}
+
+/// Check that the channels and roles of a server's configuration exist with the right
+/// types and that the bot has the permissions the commands rely on
+pub async fn check_guild(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    bot_user_id: UserId,
+) -> GuildHealth {
+    let guild_id = config.guild_id();
+    let mut checks = Vec::new();
+
+    let channels = match ctx.guild_channels(guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            checks.push(check(
+                format!("channels of server {} can be read", guild_id),
+                Some(e.to_string()),
+                CHANNEL_COMMANDS,
+            ));
+            Vec::new()
+        }
+    };
+    let roles = match ctx.guild_roles(guild_id).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            checks.push(check(
+                format!("roles of server {} can be read", guild_id),
+                Some(e.to_string()),
+                CHANNEL_COMMANDS,
+            ));
+            Vec::new()
+        }
+    };
+
+    let (category_check, category) = check_channel(
+        &channels,
+        "category",
+        config.category_id(),
+        ChannelType::Category,
+    );
+    let (log_check, log_channel) = check_channel(
+        &channels,
+        "log channel",
+        config.log_channel_id(),
+        ChannelType::Text,
+    );
+    let (list_check, list_channel) = check_channel(
+        &channels,
+        "list channel",
+        config.list_channel_id(),
+        ChannelType::Text,
+    );
+    checks.extend([category_check, log_check, list_check]);
+
+    checks.push(check_role(
+        &roles,
+        "special role",
+        config.special_role_id(),
+        SPECIAL_COMMANDS,
+    ));
+    checks.push(check_role(
+        &roles,
+        "manager role",
+        config.manager_role_id(),
+        MANAGER_COMMANDS,
+    ));
+
+    let bot_roles = match ctx.member(guild_id, bot_user_id).await {
+        Ok(member) => Some(member.roles),
+        Err(e) => {
+            eprintln!(
+                "Unable to read the bot's roles in server {}: {:?}",
+                guild_id, e
+            );
+            None
+        }
+    };
+    let bot = bot_roles
+        .as_deref()
+        .map(|member_roles| (roles.as_slice(), bot_user_id, member_roles));
+
+    checks.push(check_permissions(
+        "bot can manage channels in the category".to_string(),
+        category,
+        bot,
+        Permissions::VIEW_CHANNEL | Permissions::MANAGE_CHANNELS,
+        EDIT_COMMANDS,
+    ));
+    // Overwrites can only grant permissions the bot holds itself
+    checks.push(check_permissions(
+        "bot can set permissions on channels in the category".to_string(),
+        category,
+        bot,
+        // Private channels give the bot its own overwrite, co-owners may manage messages
+        Permissions::MANAGE_ROLES
+            | Permissions::MANAGE_MESSAGES
+            | Permissions::SEND_MESSAGES
+            | Permissions::CREATE_PUBLIC_THREADS
+            | Permissions::READ_MESSAGE_HISTORY,
+        OVERWRITE_COMMANDS,
+    ));
+    for (name, template) in &config.templates {
+        checks.push(check_permissions(
+            format!("bot can apply permission template `{}`", name),
+            category,
+            bot,
+            template.permissions(),
+            TEMPLATE_COMMANDS,
+        ));
+    }
+    checks.push(check_permissions(
+        "bot can post in the log channel".to_string(),
+        log_channel,
+        bot,
+        Permissions::VIEW_CHANNEL
+            | Permissions::SEND_MESSAGES
+            | Permissions::EMBED_LINKS
+            | Permissions::ATTACH_FILES,
+        CHANNEL_COMMANDS,
+    ));
+    checks.push(check_permissions(
+        "bot can maintain the list channel".to_string(),
+        list_channel,
+        bot,
+        Permissions::VIEW_CHANNEL
+            | Permissions::SEND_MESSAGES
+            | Permissions::EMBED_LINKS
+            | Permissions::READ_MESSAGE_HISTORY
+            | Permissions::MANAGE_MESSAGES,
+        CHANNEL_COMMANDS,
+    ));
+
+    if let Some(archive_category_id) = config.archive_category_id() {
+        let disables = if config.archive_on_delete {
+            ARCHIVE_AND_DELETE_COMMANDS
+        } else {
+            ARCHIVE_COMMANDS
+        };
+        let (mut archive_check, archive_category) = check_channel(
+            &channels,
+            "archive category",
+            archive_category_id,
+            ChannelType::Category,
+        );
+        archive_check.disables = disables;
+        checks.push(archive_check);
+        checks.push(check_permissions(
+            "bot can move channels to the archive category".to_string(),
+            archive_category,
+            bot,
+            Permissions::VIEW_CHANNEL | Permissions::MANAGE_CHANNELS | Permissions::MANAGE_ROLES,
+            disables,
+        ));
+    }
+
+    if let Some(trash_category_id) = config.trash_category_id() {
+        let (mut trash_check, trash_category) = check_channel(
+            &channels,
+            "trash category",
+            trash_category_id,
+            ChannelType::Category,
+        );
+        trash_check.disables = TRASH_COMMANDS;
+        checks.push(trash_check);
+        checks.push(check_permissions(
+            "bot can move channels to the trash category".to_string(),
+            trash_category,
+            bot,
+            // Transcripts are exported when trashed channels are purged
+            Permissions::VIEW_CHANNEL
+                | Permissions::MANAGE_CHANNELS
+                | Permissions::MANAGE_ROLES
+                | Permissions::READ_MESSAGE_HISTORY,
+            TRASH_COMMANDS,
+        ));
+    }
+
+    GuildHealth {
+        config: config.clone(),
+        checks,
+    }
+}
@@ -0,0 +1,12 @@
+pub mod backend;
+pub mod commands;
+pub mod config;
+pub mod embed;
+pub mod health;
+pub mod naming;
+pub mod registration;
+pub mod registry;
+pub mod reload;
+pub mod transcript;
+pub mod trash;
+pub mod utils;
\ No newline at end of file
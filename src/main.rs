@@ -1,43 +1,153 @@
-mod commands;
-mod config;
-mod embed;
-mod utils;
-
-use anyhow::Result;
-use serenity::all::{
-    Command, CreateInteractionResponse, CreateInteractionResponseMessage, GatewayIntents,
-    Interaction, Ready,
-};
+use anyhow::{bail, Context as AnyhowContext, Result};
+use serenity::all::{GatewayIntents, GuildId, Interaction, Ready};
 use serenity::async_trait;
 use serenity::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-use config::Config;
+use discord_channel_bot::backend::{Backend, CommandRequest, SerenityBackend};
+use discord_channel_bot::commands::{CommandContext, CommandSet};
+use discord_channel_bot::config::{Config, GuildConfig, SharedConfig, ENV_PREFIX};
+use discord_channel_bot::health::{check_guild, GuildHealth};
+use discord_channel_bot::registration::register_commands;
+use discord_channel_bot::registry::ChannelRegistry;
+use discord_channel_bot::reload::watch_config;
+use discord_channel_bot::trash::watch_trash;
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// How long a failed configuration check is trusted before running it again,
+/// so fixes made in Discord take effect without a restart
+const FAILED_CHECK_TTL: Duration = Duration::from_secs(60);
+
+/// A configuration check and when it was made
+struct CachedHealth {
+    health: GuildHealth,
+    checked_at: Instant,
+    /// Reload generation of the shared configuration at the time of the check
+    generation: u64,
+}
+
+impl CachedHealth {
+    fn is_fresh(&self, config: &GuildConfig, generation: u64) -> bool {
+        self.health.config == *config
+            && self.generation == generation
+            && (self.health.is_healthy() || self.checked_at.elapsed() < FAILED_CHECK_TTL)
+    }
+}
 
 struct Handler {
-    config: Config,
+    config: Arc<SharedConfig>,
+    registry: Arc<ChannelRegistry>,
+    commands: CommandSet,
+    /// Latest configuration check of each server
+    health: RwLock<HashMap<u64, CachedHealth>>,
+}
+
+impl Handler {
+    /// Check a server's configuration against the guild, print the report and keep it
+    async fn check_guild(&self, ctx: &Context, config: &GuildConfig) -> GuildHealth {
+        let generation = self.config.generation();
+        let bot_user_id = ctx.cache.current_user().id;
+        let backend = SerenityBackend::new(ctx.http.clone());
+        let health = check_guild(&backend, config, bot_user_id).await;
+        println!("{}", health.report());
+
+        self.health.write().unwrap().insert(
+            config.guild_id,
+            CachedHealth {
+                health: health.clone(),
+                checked_at: Instant::now(),
+                generation,
+            },
+        );
+        health
+    }
+
+    /// Why `command` cannot run in a server, checking again after a reload or when
+    /// a failed check has expired
+    async fn disabled_reason(
+        &self,
+        ctx: &Context,
+        config: &GuildConfig,
+        command: &str,
+    ) -> Option<String> {
+        let generation = self.config.generation();
+        let cached = self
+            .health
+            .read()
+            .unwrap()
+            .get(&config.guild_id)
+            .filter(|c| c.is_fresh(config, generation))
+            .map(|c| c.health.clone());
+        let health = match cached {
+            Some(health) => health,
+            None => self.check_guild(ctx, config).await,
+        };
+        health.disabled_reason(command)
+    }
 }
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            let result = match command.data.name.as_str() {
-                "create" => commands::create::run(&ctx, &command, &self.config).await,
-                "delete" => commands::delete::run(&ctx, &command, &self.config).await,
-                "rename" => commands::rename::run(&ctx, &command, &self.config).await,
-                "description" => commands::description::run(&ctx, &command, &self.config).await,
-                "updatelist" => commands::updatelist::run(&ctx, &command, &self.config).await,
-                _ => Ok(()),
+        // Button clicks are routed to the command that sent the buttons
+        let command = match &interaction {
+            Interaction::Command(command) => Some(CommandRequest::from_interaction(command)),
+            Interaction::Component(component) => Some(CommandRequest::from_component(component)),
+            _ => None,
+        };
+
+        if let Some(command) = command {
+            let backend = SerenityBackend::new(ctx.http.clone());
+
+            // Snapshot the configuration so a reload cannot change it mid-command
+            let shared_config = self.config.current();
+
+            // Each server has its own settings, lists and logs
+            let Some(config) = command.guild_id.and_then(|id| shared_config.guild(id)) else {
+                let _ = backend
+                    .respond(
+                        &command.interaction,
+                        "This server is not configured for this bot!",
+                    )
+                    .await;
+                return;
             };
 
+            // Commands that cannot work with the current settings are refused upfront
+            if let Some(reason) = self.disabled_reason(&ctx, config, &command.name).await {
+                let _ = backend
+                    .respond(
+                        &command.interaction,
+                        &format!(
+                            "This command is disabled until the bot configuration of this server is fixed: {}",
+                            reason
+                        ),
+                    )
+                    .await;
+                return;
+            }
+
+            let bot_user_id = ctx.cache.current_user().id;
+            let result = self
+                .commands
+                .dispatch(CommandContext {
+                    backend: &backend,
+                    request: &command,
+                    config,
+                    registry: &self.registry,
+                    shared_config: &self.config,
+                    bot_user_id,
+                })
+                .await;
+
             if let Err(e) = result {
                 eprintln!("Error executing command: {:?}", e);
-                let response = CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content(format!("Error: {}", e))
-                        .ephemeral(true),
-                );
-                let _ = command.create_response(&ctx.http, response).await;
+                let _ = backend
+                    .respond(&command.interaction, &format!("Error: {}", e))
+                    .await;
             }
         }
     }
@@ -45,36 +155,69 @@ impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
         println!("{} connected!", ready.user.name);
 
-        let commands = vec![
-            commands::create::register(),
-            commands::delete::register(),
-            commands::rename::register(),
-            commands::description::register(),
-            commands::updatelist::register(),
-        ];
-
-        for command in commands {
-            if let Err(e) = Command::create_global_command(&ctx.http, command).await {
-                eprintln!("Error registering command: {:?}", e);
-            }
+        let commands = self.commands.register_all();
+
+        let config = self.config.current();
+        let guild_ids: Vec<GuildId> = config.guilds.keys().map(|id| GuildId::new(*id)).collect();
+        register_commands(&ctx.http, config.command_scope, &guild_ids, commands).await;
+
+        // Check every configured server before users start running commands
+        for guild in config.guilds.values() {
+            self.check_guild(&ctx, guild).await;
         }
+    }
+}
 
-        println!("Commands registered successfully!");
+/// Config file path from `--config <path>`, then `CHANNEL_BOT_CONFIG`, then the default.
+/// An explicitly chosen file must exist; the default one may be replaced by environment
+/// variables entirely.
+fn config_path() -> Result<String> {
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            path = Some(args.next().context("--config requires a path")?);
+        } else if let Some(value) = arg.strip_prefix("--config=") {
+            path = Some(value.to_string());
+        } else {
+            bail!("Unknown argument `{}`", arg);
+        }
+    }
+
+    let path = path.or_else(|| std::env::var(format!("{}CONFIG", ENV_PREFIX)).ok());
+    match path {
+        Some(path) if !std::path::Path::new(&path).exists() => {
+            bail!("Config file {} not found", path)
+        }
+        Some(path) => Ok(path),
+        None => Ok(DEFAULT_CONFIG_PATH.to_string()),
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let config = Config::load("config.toml")?;
+    let config_path = config_path()?;
+    let config = Config::load(&config_path)?;
+    let registry = Arc::new(ChannelRegistry::load(&config.registry_path)?);
 
     let intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_MESSAGES;
 
-    let mut client = Client::builder(&config.token, intents)
+    let token = config.token.clone();
+    let shared_config = Arc::new(SharedConfig::new(&config_path, config));
+
+    let mut client = Client::builder(&token, intents)
         .event_handler(Handler {
-            config: config.clone(),
+            config: shared_config.clone(),
+            registry: registry.clone(),
+            commands: CommandSet::new(),
+            health: RwLock::new(HashMap::new()),
         })
         .await?;
 
+    let backend: Arc<dyn Backend> = Arc::new(SerenityBackend::new(client.http.clone()));
+    tokio::spawn(watch_config(backend.clone(), shared_config.clone()));
+    tokio::spawn(watch_trash(backend, shared_config, registry));
+
     println!("Bot is starting...");
     client.start().await?;
 
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use serenity::all::ChannelType;
+use std::fmt;
+
+use crate::backend::Backend;
+use crate::config::{GuildConfig, NamingPolicy};
+use crate::registry::ChannelRegistry;
+use crate::utils::get_managed_channels;
+
+/// Longest channel name Discord accepts
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Why a channel name was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameError {
+    /// Nothing usable is left after normalization
+    Empty,
+    TooLong {
+        length: usize,
+    },
+    /// Another managed channel already uses the name
+    Taken {
+        channel_id: u64,
+    },
+    /// The log or list channel uses the name
+    Reserved {
+        channel_id: u64,
+    },
+    MissingPrefix {
+        prefix: String,
+    },
+    PatternMismatch {
+        pattern: String,
+    },
+    /// The naming policy reserves the name
+    ReservedName,
+    BlockedWord {
+        word: String,
+    },
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(
+                f,
+                "Channel name must contain at least one letter, digit, `-` or `_`!"
+            ),
+            NameError::TooLong { length } => write!(
+                f,
+                "Channel name is {} characters long, the maximum is {}!",
+                length, MAX_NAME_LENGTH
+            ),
+            NameError::Taken { channel_id } => {
+                write!(
+                    f,
+                    "A channel with this name already exists: <#{}>",
+                    channel_id
+                )
+            }
+            NameError::Reserved { channel_id } => write!(
+                f,
+                "This name is used by the bot's own channel <#{}>!",
+                channel_id
+            ),
+            NameError::MissingPrefix { prefix } => {
+                write!(f, "Channel names must start with `{}`!", prefix)
+            }
+            NameError::PatternMismatch { pattern } => write!(
+                f,
+                "Channel name does not match the required pattern `{}`!",
+                pattern
+            ),
+            NameError::ReservedName => write!(f, "This channel name is reserved!"),
+            NameError::BlockedWord { word } => {
+                write!(f, "Channel name contains the blocked word `{}`!", word)
+            }
+        }
+    }
+}
+
+/// Whether Discord lowercases the names of channels of `kind` and replaces their spaces
+pub fn has_text_name(kind: ChannelType) -> bool {
+    matches!(
+        kind,
+        ChannelType::Text | ChannelType::News | ChannelType::Forum
+    )
+}
+
+/// Turn user input into the name Discord will give a channel of `kind`. Text,
+/// announcement and forum names are lowercased, spaces become dashes, characters other
+/// than letters, digits, `-` and `_` are dropped, and repeated or surrounding dashes are
+/// removed. Other names, such as voice channels', are only trimmed.
+pub fn normalize_channel_name(input: &str, kind: ChannelType) -> String {
+    if !has_text_name(kind) {
+        return input.trim().to_string();
+    }
+    let mut name = String::with_capacity(input.len());
+    for c in input.chars().flat_map(char::to_lowercase) {
+        let c = if c.is_whitespace() { '-' } else { c };
+        if !(c.is_alphanumeric() || c == '-' || c == '_') {
+            continue;
+        }
+        if c == '-' && (name.is_empty() || name.ends_with('-')) {
+            continue;
+        }
+        name.push(c);
+    }
+    while name.ends_with('-') {
+        name.pop();
+    }
+    name
+}
+
+/// Normalize a required prefix the way it would appear in a channel name, keeping
+/// the separator it ends with (`Proj ` becomes `proj-`)
+pub fn normalize_prefix(prefix: &str) -> String {
+    let mut normalized = normalize_channel_name(&format!("{}x", prefix), ChannelType::Text);
+    normalized.pop();
+    normalized
+}
+
+/// Check a name normalized for a text channel against the server's naming rules
+pub fn check_policy(policy: &NamingPolicy, name: &str) -> Result<(), NameError> {
+    if let Some(prefix) = &policy.required_prefix {
+        let prefix = normalize_prefix(prefix);
+        if !name.starts_with(prefix.as_str()) {
+            return Err(NameError::MissingPrefix { prefix });
+        }
+    }
+    if let Some(pattern) = &policy.pattern {
+        // Patterns are checked when the configuration is loaded
+        if let Ok(regex) = regex::Regex::new(pattern) {
+            if !regex.is_match(name) {
+                return Err(NameError::PatternMismatch {
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+    }
+    if policy
+        .reserved_names
+        .iter()
+        .any(|n| normalize_channel_name(n, ChannelType::Text) == name)
+    {
+        return Err(NameError::ReservedName);
+    }
+    if let Some(word) = policy
+        .blocked_words
+        .iter()
+        .find(|w| !w.is_empty() && name.contains(&w.to_lowercase()))
+    {
+        return Err(NameError::BlockedWord { word: word.clone() });
+    }
+    Ok(())
+}
+
+/// Normalize `input` for a channel of `kind` and check it against the naming rules and
+/// the names already in use.
+///
+/// `policy` is `None` for members exempt from the naming rules, which apply to the name
+/// as a text channel would spell it. `managed` are the managed channels, `reserved` the
+/// log and list channels, both as `(channel id, name)`; names are compared ignoring case
+/// since voice names keep theirs. `renaming` is the channel being renamed, which may
+/// keep its name.
+pub fn validate_channel_name(
+    input: &str,
+    kind: ChannelType,
+    policy: Option<&NamingPolicy>,
+    managed: &[(u64, String)],
+    reserved: &[(u64, String)],
+    renaming: Option<u64>,
+) -> Result<String, NameError> {
+    let name = normalize_channel_name(input, kind);
+
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+    let length = name.chars().count();
+    if length > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong { length });
+    }
+    if let Some(policy) = policy {
+        check_policy(policy, &normalize_channel_name(&name, ChannelType::Text))?;
+    }
+
+    let same_name = |n: &String| n.to_lowercase() == name.to_lowercase();
+    if let Some((channel_id, _)) = reserved.iter().find(|(_, n)| same_name(n)) {
+        return Err(NameError::Reserved {
+            channel_id: *channel_id,
+        });
+    }
+    if let Some((channel_id, _)) = managed
+        .iter()
+        .find(|(id, n)| same_name(n) && Some(*id) != renaming)
+    {
+        return Err(NameError::Taken {
+            channel_id: *channel_id,
+        });
+    }
+
+    Ok(name)
+}
+
+/// Check a requested name for a channel of `kind` against the naming rules, unless
+/// `apply_policy` is false, and the channels currently in the server
+pub async fn check_channel_name(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    input: &str,
+    kind: ChannelType,
+    apply_policy: bool,
+    renaming: Option<u64>,
+) -> Result<Result<String, NameError>> {
+    let managed: Vec<(u64, String)> = get_managed_channels(ctx, config, registry)
+        .await?
+        .into_iter()
+        .map(|c| (c.channel_id, c.name))
+        .collect();
+
+    let mut reserved = Vec::new();
+    for channel_id in [config.log_channel_id(), config.list_channel_id()] {
+        if let Ok(channel) = ctx.channel(channel_id).await {
+            reserved.push((channel.id.get(), channel.name));
+        }
+    }
+
+    let policy = apply_policy.then_some(&config.naming);
+    Ok(validate_channel_name(
+        input, kind, policy, &managed, &reserved, renaming,
+    ))
+}
+
+/// Text announcing the final name, mentioning the input when it had to be adjusted
+pub fn describe_name(input: &str, name: &str) -> String {
+    if input == name {
+        format!("#{}", name)
+    } else {
+        format!("#{} (adjusted from `{}`)", name, input)
+    }
+}
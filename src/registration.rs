@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serenity::all::{Command, CreateCommand, GuildId, Http};
+
+use crate::config::CommandScope;
+
+/// A slash command as currently registered on Discord
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredCommand {
+    pub name: String,
+    /// Bumped by Discord whenever the command definition changes
+    pub version: u64,
+}
+
+impl From<&Command> for RegisteredCommand {
+    fn from(command: &Command) -> Self {
+        Self {
+            name: command.name.clone(),
+            version: command.version.get(),
+        }
+    }
+}
+
+/// What a bulk overwrite changed in a set of registered commands
+#[derive(Debug, Default, PartialEq)]
+pub struct CommandChanges {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl CommandChanges {
+    /// Compare the commands registered before and after an overwrite
+    pub fn between(before: &[RegisteredCommand], after: &[RegisteredCommand]) -> Self {
+        let mut changes = Self::default();
+        for command in after {
+            match before.iter().find(|c| c.name == command.name) {
+                None => changes.added.push(command.name.clone()),
+                Some(old) if old.version != command.version => {
+                    changes.updated.push(command.name.clone())
+                }
+                Some(_) => changes.unchanged += 1,
+            }
+        }
+        for command in before {
+            if !after.iter().any(|c| c.name == command.name) {
+                changes.removed.push(command.name.clone());
+            }
+        }
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    /// One line summary, e.g. `added /a; removed /b; 4 unchanged`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        for (label, names) in [
+            ("added", &self.added),
+            ("updated", &self.updated),
+            ("removed", &self.removed),
+        ] {
+            if !names.is_empty() {
+                let names: Vec<String> = names.iter().map(|n| format!("/{}", n)).collect();
+                parts.push(format!("{} {}", label, names.join(", ")));
+            }
+        }
+        if self.unchanged > 0 || parts.is_empty() {
+            parts.push(format!("{} unchanged", self.unchanged));
+        }
+        parts.join("; ")
+    }
+}
+
+/// Replace the global commands, or those of `guild_id`, with exactly `commands`
+async fn overwrite(
+    http: &Http,
+    guild_id: Option<GuildId>,
+    commands: Vec<CreateCommand>,
+) -> Result<CommandChanges> {
+    let before = match guild_id {
+        Some(guild_id) => guild_id.get_commands(http).await?,
+        None => Command::get_global_commands(http).await?,
+    };
+    // Nothing to register and nothing to remove
+    if before.is_empty() && commands.is_empty() {
+        return Ok(CommandChanges::default());
+    }
+
+    let after = match guild_id {
+        Some(guild_id) => guild_id.set_commands(http, commands).await?,
+        None => Command::set_global_commands(http, commands).await?,
+    };
+
+    let before: Vec<RegisteredCommand> = before.iter().map(RegisteredCommand::from).collect();
+    let after: Vec<RegisteredCommand> = after.iter().map(RegisteredCommand::from).collect();
+    Ok(CommandChanges::between(&before, &after))
+}
+
+fn report(target: &str, result: Result<CommandChanges>) {
+    match result {
+        Ok(changes) => println!("{} commands: {}", target, changes.summary()),
+        Err(e) => eprintln!("Error registering {} commands: {:?}", target, e),
+    }
+}
+
+/// Register `commands` in `scope` and remove the ones left in the other scope, so the
+/// commands users see match the code exactly
+pub async fn register_commands(
+    http: &Http,
+    scope: CommandScope,
+    guild_ids: &[GuildId],
+    commands: Vec<CreateCommand>,
+) {
+    let (global, guild) = match scope {
+        CommandScope::Global => (commands, Vec::new()),
+        CommandScope::Guild => (Vec::new(), commands),
+    };
+
+    report("Global", overwrite(http, None, global).await);
+    for guild_id in guild_ids {
+        let target = format!("Server {}", guild_id);
+        report(
+            &target,
+            overwrite(http, Some(*guild_id), guild.clone()).await,
+        );
+    }
+}
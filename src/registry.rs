@@ -0,0 +1,532 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::all::{PermissionOverwrite, PermissionOverwriteType, RoleId, UserId};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use crate::utils::{has_topic, ChannelInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelStatus {
+    Active,
+    /// Moved to the archive category, kept out of the channel list
+    Archived,
+    /// Moved to the trash category, waiting to be purged
+    Trashed,
+    Deleted,
+}
+
+/// Where a trashed channel came from and when it is purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub trashed_at: DateTime<Utc>,
+    pub trashed_by: u64,
+    pub purge_at: DateTime<Utc>,
+    /// Overwrites the channel had before, put back when it is restored
+    pub permission_overwrites: Vec<PermissionOverwrite>,
+    /// Why the last purge failed, reported to the log channel once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purge_error: Option<String>,
+}
+
+/// A member or role allowed to post in a channel it does not own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contributor {
+    User(u64),
+    Role(u64),
+}
+
+impl Contributor {
+    pub fn mention(&self) -> String {
+        match self {
+            Contributor::User(id) => format!("<@{}>", id),
+            Contributor::Role(id) => format!("<@&{}>", id),
+        }
+    }
+
+    /// Target of the contributor's permission overwrite
+    pub fn overwrite_kind(&self) -> PermissionOverwriteType {
+        match self {
+            Contributor::User(id) => PermissionOverwriteType::Member(UserId::new(*id)),
+            Contributor::Role(id) => PermissionOverwriteType::Role(RoleId::new(*id)),
+        }
+    }
+}
+
+/// A single change to a channel name or description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub value: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    /// User who made the change, `None` when it was done outside the bot
+    pub changed_by: Option<u64>,
+}
+
+/// Everything the bot remembers about a managed channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRecord {
+    pub channel_id: u64,
+    /// Server the channel belongs to, 0 for records written before multi-guild support
+    #[serde(default)]
+    pub guild_id: u64,
+    pub creator_id: u64,
+    /// Member the channel was transferred to, the creator owns it while unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<u64>,
+    /// Members sharing the management of the channel with its owner
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_owners: Vec<u64>,
+    /// Members and roles allowed to post without owning the channel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+    /// Members let into the channel while it is private
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invited: Vec<u64>,
+    /// Permission template the channel was created from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub status: ChannelStatus,
+    pub name_history: Vec<HistoryEntry>,
+    pub description_history: Vec<HistoryEntry>,
+    /// Set while the channel is in the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash: Option<TrashEntry>,
+    /// Permission overwrites from before the channel was archived, set while it is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_overwrites: Option<Vec<PermissionOverwrite>>,
+}
+
+impl ChannelRecord {
+    fn new(guild_id: u64, channel_id: u64, creator_id: u64, name: &str, by: Option<u64>) -> Self {
+        let now = Utc::now();
+        Self {
+            channel_id,
+            guild_id,
+            creator_id,
+            owner: None,
+            co_owners: Vec::new(),
+            contributors: Vec::new(),
+            invited: Vec::new(),
+            template: None,
+            created_at: now,
+            status: ChannelStatus::Active,
+            name_history: vec![HistoryEntry {
+                value: Some(name.to_string()),
+                changed_at: now,
+                changed_by: by,
+            }],
+            description_history: Vec::new(),
+            trash: None,
+            archived_overwrites: None,
+        }
+    }
+
+    /// User allowed to manage the channel, 0 when unknown
+    pub fn owner_id(&self) -> u64 {
+        self.owner.unwrap_or(self.creator_id)
+    }
+
+    /// Owner followed by the co-owners, leaving out an unknown owner
+    pub fn owner_ids(&self) -> Vec<u64> {
+        std::iter::once(self.owner_id())
+            .chain(self.co_owners.iter().copied())
+            .filter(|&id| id != 0)
+            .collect()
+    }
+
+    /// Whether `user_id` is the owner or one of the co-owners
+    pub fn is_owned_by(&self, user_id: u64) -> bool {
+        user_id != 0 && (self.owner_id() == user_id || self.co_owners.contains(&user_id))
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name_history.last().and_then(|e| e.value.as_deref())
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description_history
+            .last()
+            .and_then(|e| e.value.as_deref())
+    }
+
+    fn push_name(&mut self, name: &str, by: Option<u64>) -> bool {
+        if self.name() == Some(name) {
+            return false;
+        }
+        self.name_history.push(HistoryEntry {
+            value: Some(name.to_string()),
+            changed_at: Utc::now(),
+            changed_by: by,
+        });
+        true
+    }
+
+    fn push_description(&mut self, description: Option<&str>, by: Option<u64>) -> bool {
+        let description = description.filter(|d| !d.is_empty());
+        if self.description() == description {
+            return false;
+        }
+        self.description_history.push(HistoryEntry {
+            value: description.map(str::to_string),
+            changed_at: Utc::now(),
+            changed_by: by,
+        });
+        true
+    }
+}
+
+/// A message in the list channel and the entries it currently renders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSlot {
+    pub message_id: u64,
+    #[serde(default)]
+    pub channel_ids: Vec<u64>,
+    #[serde(default)]
+    pub embeds: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryData {
+    channels: BTreeMap<u64, ChannelRecord>,
+    /// List messages in display order, keyed by list channel id
+    #[serde(default)]
+    list_slots: BTreeMap<u64, Vec<ListSlot>>,
+}
+
+/// Durable store of managed channels, persisted as JSON on disk
+pub struct ChannelRegistry {
+    path: PathBuf,
+    data: Mutex<RegistryData>,
+}
+
+impl ChannelRegistry {
+    /// Load the registry from `path`, starting empty if the file does not exist yet
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let data = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content)?
+        } else {
+            RegistryData::default()
+        };
+
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+
+    /// Write the registry to a temporary file and move it into place
+    async fn save(&self, data: &RegistryData) -> Result<()> {
+        let content = serde_json::to_string_pretty(data)?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, channel_id: u64) -> Option<ChannelRecord> {
+        self.data.lock().await.channels.get(&channel_id).cloned()
+    }
+
+    pub async fn record_created(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+        creator_id: u64,
+        name: &str,
+        template: Option<&str>,
+    ) -> Result<()> {
+        let mut data = self.data.lock().await;
+        let mut record =
+            ChannelRecord::new(guild_id, channel_id, creator_id, name, Some(creator_id));
+        record.template = template.map(str::to_string);
+        data.channels.insert(channel_id, record);
+        self.save(&data).await
+    }
+
+    pub async fn record_renamed(&self, channel_id: u64, name: &str, by: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if record.push_name(name, Some(by)) {
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_description(
+        &self,
+        channel_id: u64,
+        description: Option<&str>,
+        by: u64,
+    ) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if record.push_description(description, Some(by)) {
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_deleted(&self, channel_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.status = ChannelStatus::Deleted;
+            record.trash = None;
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    /// Mark a channel as archived under its new `name`, keeping the `overwrites` it had
+    pub async fn record_archived(
+        &self,
+        channel_id: u64,
+        name: &str,
+        by: u64,
+        overwrites: Vec<PermissionOverwrite>,
+    ) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.push_name(name, Some(by));
+            record.status = ChannelStatus::Archived;
+            record.archived_overwrites = Some(overwrites);
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    /// Mark an archived channel as active again under its restored `name`
+    pub async fn record_unarchived(&self, channel_id: u64, name: &str, by: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.push_name(name, Some(by));
+            record.status = ChannelStatus::Active;
+            record.archived_overwrites = None;
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn record_transferred(&self, channel_id: u64, owner_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.owner = Some(owner_id);
+            record.co_owners.retain(|&id| id != owner_id);
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn record_co_owner_added(&self, channel_id: u64, user_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if !record.co_owners.contains(&user_id) {
+                record.co_owners.push(user_id);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_co_owner_removed(&self, channel_id: u64, user_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if record.co_owners.contains(&user_id) {
+                record.co_owners.retain(|&id| id != user_id);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_contributor_added(
+        &self,
+        channel_id: u64,
+        contributor: Contributor,
+    ) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if !record.contributors.contains(&contributor) {
+                record.contributors.push(contributor);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_contributor_removed(
+        &self,
+        channel_id: u64,
+        contributor: Contributor,
+    ) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if record.contributors.contains(&contributor) {
+                record.contributors.retain(|&c| c != contributor);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_invited(&self, channel_id: u64, user_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if !record.invited.contains(&user_id) {
+                record.invited.push(user_id);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_kicked(&self, channel_id: u64, user_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            if record.invited.contains(&user_id) {
+                record.invited.retain(|&id| id != user_id);
+                self.save(&data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn record_trashed(&self, channel_id: u64, entry: TrashEntry) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.status = ChannelStatus::Trashed;
+            record.trash = Some(entry);
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    /// Mark a trashed channel as active again
+    pub async fn record_restored(&self, channel_id: u64) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.channels.get_mut(&channel_id) {
+            record.status = ChannelStatus::Active;
+            record.trash = None;
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    /// Remember why a trashed channel could not be purged, it stays due
+    pub async fn record_purge_failed(&self, channel_id: u64, error: String) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(trash) = data
+            .channels
+            .get_mut(&channel_id)
+            .and_then(|r| r.trash.as_mut())
+        {
+            trash.purge_error = Some(error);
+            self.save(&data).await?;
+        }
+        Ok(())
+    }
+
+    /// Trashed channels of a guild whose grace period ended by `now`
+    pub async fn due_purges(&self, guild_id: u64, now: DateTime<Utc>) -> Vec<ChannelRecord> {
+        self.data
+            .lock()
+            .await
+            .channels
+            .values()
+            .filter(|r| r.guild_id == guild_id && r.status == ChannelStatus::Trashed)
+            .filter(|r| r.trash.as_ref().is_some_and(|t| t.purge_at <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Messages currently rendering the channel list, in display order
+    pub async fn list_slots(&self, list_channel_id: u64) -> Vec<ListSlot> {
+        self.data
+            .lock()
+            .await
+            .list_slots
+            .get(&list_channel_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub async fn set_list_slots(&self, list_channel_id: u64, slots: Vec<ListSlot>) -> Result<()> {
+        let mut data = self.data.lock().await;
+        data.list_slots.insert(list_channel_id, slots);
+        self.save(&data).await
+    }
+
+    /// Reconcile the registry with the channels currently present on Discord in a guild.
+    ///
+    /// Unknown channels are adopted using the owners guessed from their overwrites,
+    /// out-of-band renames and topic changes are appended to the history, and active
+    /// records of this guild whose channel is no longer in the category are marked as
+    /// deleted. Archived and trashed records live outside the category and are left alone.
+    /// The returned list carries the owners stored in the registry rather than the guessed ones.
+    pub async fn reconcile(
+        &self,
+        guild_id: u64,
+        observed: Vec<ChannelInfo>,
+    ) -> Result<Vec<ChannelInfo>> {
+        let mut data = self.data.lock().await;
+        let mut changed = false;
+
+        let mut channels = Vec::with_capacity(observed.len());
+        for mut info in observed {
+            let record = data.channels.entry(info.channel_id).or_insert_with(|| {
+                changed = true;
+                let mut record =
+                    ChannelRecord::new(guild_id, info.channel_id, info.owner_id, &info.name, None);
+                record.co_owners = info.co_owner_ids.clone();
+                record
+            });
+
+            if record.guild_id != guild_id {
+                record.guild_id = guild_id;
+                changed = true;
+            }
+            if record.status != ChannelStatus::Active {
+                // Moved back into the category by hand
+                record.status = ChannelStatus::Active;
+                record.trash = None;
+                changed = true;
+            }
+            changed |= record.push_name(&info.name, None);
+            if has_topic(info.kind) {
+                changed |= record.push_description(info.description.as_deref(), None);
+            } else {
+                // Only the registry knows the description of channels without a topic
+                info.description = record.description().map(str::to_string);
+            }
+
+            info.owner_id = record.owner_id();
+            info.co_owner_ids = record.co_owners.clone();
+            channels.push(info);
+        }
+
+        for record in data.channels.values_mut() {
+            if record.guild_id == guild_id
+                && record.status == ChannelStatus::Active
+                && !channels.iter().any(|c| c.channel_id == record.channel_id)
+            {
+                record.status = ChannelStatus::Deleted;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.save(&data).await?;
+        }
+
+        Ok(channels)
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::backend::Backend;
+use crate::config::{Config, ConfigChange, SharedConfig};
+use crate::embed::{send_log_embed, EmbedColor};
+
+/// How often the config file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reload the config file and report the result to the log channel of every server.
+///
+/// On success each server receives the changes that concern it plus the bot-wide ones.
+/// When the new file is invalid the current configuration stays in place and the error
+/// is reported instead.
+pub async fn reload_and_report(
+    ctx: &dyn Backend,
+    shared: &SharedConfig,
+    user_id: Option<u64>,
+) -> Result<Vec<ConfigChange>> {
+    let by = user_id
+        .map(|id| format!(" by <@{}>", id))
+        .unwrap_or_default();
+
+    match shared.reload() {
+        Ok((old, changes)) => {
+            if !changes.is_empty() {
+                report_changes(ctx, &old, &shared.current(), &changes, &by).await;
+            }
+            Ok(changes)
+        }
+        Err(e) => {
+            let current = shared.current();
+            for guild in current.guilds.values() {
+                let description = format!(
+                    "Configuration reload{} failed, keeping the current configuration: {}",
+                    by, e
+                );
+                let _ = send_log_embed(
+                    ctx,
+                    guild.log_channel_id(),
+                    &description,
+                    Some(EmbedColor::Red.value()),
+                )
+                .await;
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn report_changes(
+    ctx: &dyn Backend,
+    old: &Config,
+    new: &Config,
+    changes: &[ConfigChange],
+    by: &str,
+) {
+    let guild_ids: BTreeSet<&u64> = old.guilds.keys().chain(new.guilds.keys()).collect();
+    for guild_id in guild_ids {
+        // Removed servers are told through their old log channel
+        let Some(guild) = new
+            .guilds
+            .get(guild_id)
+            .or_else(|| old.guilds.get(guild_id))
+        else {
+            continue;
+        };
+
+        let lines: Vec<String> = changes
+            .iter()
+            .filter(|c| c.guild_id.is_none() || c.guild_id == Some(*guild_id))
+            .map(|c| format!("- {}", c.description))
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        let description = format!("Configuration reloaded{}\n{}", by, lines.join("\n"));
+        if let Err(e) = send_log_embed(
+            ctx,
+            guild.log_channel_id(),
+            &description,
+            Some(EmbedColor::Blue.value()),
+        )
+        .await
+        {
+            eprintln!("Error reporting config changes: {:?}", e);
+        }
+    }
+}
+
+fn modified_at(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload the config whenever the file is modified on disk
+pub async fn watch_config(ctx: Arc<dyn Backend>, shared: Arc<SharedConfig>) {
+    let mut last_modified = modified_at(shared.path());
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let modified = modified_at(shared.path());
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        match reload_and_report(ctx.as_ref(), &shared, None).await {
+            Ok(changes) => println!("Config reloaded ({} changes)", changes.len()),
+            Err(e) => eprintln!("Config reload failed: {:?}", e),
+        }
+    }
+}
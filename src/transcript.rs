@@ -0,0 +1,278 @@
+use anyhow::Result;
+use serde::Serialize;
+use serenity::all::{ChannelId, ChannelType};
+
+use crate::backend::{is_too_large, AttachmentData, Backend, FileData, MessageData};
+
+/// Full history of a channel and its threads, as exported before deletion
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub channel_id: u64,
+    pub name: String,
+    pub topic: Option<String>,
+    /// RFC 3339 time of the export
+    pub exported_at: String,
+    pub messages: Vec<TranscriptMessage>,
+    pub threads: Vec<ThreadTranscript>,
+    /// Position of this part when the transcript was too large to upload at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadTranscript {
+    pub thread_id: u64,
+    pub name: String,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptMessage {
+    pub message_id: u64,
+    pub author_id: u64,
+    pub author_name: String,
+    pub timestamp: String,
+    pub content: String,
+    /// Descriptions of the message's embeds
+    pub embeds: Vec<String>,
+    pub attachments: Vec<AttachmentData>,
+}
+
+impl From<MessageData> for TranscriptMessage {
+    fn from(message: MessageData) -> Self {
+        Self {
+            message_id: message.id.get(),
+            author_id: message.author_id.get(),
+            author_name: message.author_name,
+            timestamp: message.timestamp.to_string(),
+            content: message.content,
+            embeds: message.embeds.into_iter().map(|e| e.description).collect(),
+            attachments: message.attachments,
+        }
+    }
+}
+
+impl Transcript {
+    /// e.g. `Transcript of #name: 12 message(s), 1 thread(s)`, or `Transcript of
+    /// #name, part 2: ...` for a part
+    pub fn summary(&self) -> String {
+        let part = self
+            .part
+            .map(|p| format!(", part {}", p))
+            .unwrap_or_default();
+        format!(
+            "Transcript of #{}{}: {} message(s), {} thread(s)",
+            self.name,
+            part,
+            self.message_count(),
+            self.threads.len()
+        )
+    }
+
+    /// Number of messages in the channel and its threads
+    pub fn message_count(&self) -> usize {
+        self.messages.len() + self.threads.iter().map(|t| t.messages.len()).sum::<usize>()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Readable version of the transcript
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec![
+            format!("# Transcript of #{}", self.name),
+            String::new(),
+            format!("- Channel id: {}", self.channel_id),
+        ];
+        if let Some(topic) = &self.topic {
+            lines.push(format!("- Description: {}", topic));
+        }
+        lines.push(format!("- Exported at: {}", self.exported_at));
+        lines.push(format!(
+            "- {} message(s), {} thread(s)",
+            self.message_count(),
+            self.threads.len()
+        ));
+
+        lines.push(String::new());
+        lines.push("## Messages".to_string());
+        push_messages(&mut lines, &self.messages);
+
+        for thread in &self.threads {
+            lines.push(String::new());
+            lines.push(format!("## Thread: {}", thread.name));
+            push_messages(&mut lines, &thread.messages);
+        }
+        lines.join("\n")
+    }
+
+    /// The JSON and Markdown files to upload
+    pub fn files(&self) -> Result<Vec<FileData>> {
+        let mut base = format!("transcript-{}-{}", self.name, self.channel_id);
+        if let Some(part) = self.part {
+            base.push_str(&format!("-part{}", part));
+        }
+        Ok(vec![
+            FileData {
+                name: format!("{}.json", base),
+                data: self.to_json()?.into_bytes(),
+            },
+            FileData {
+                name: format!("{}.md", base),
+                data: self.to_markdown().into_bytes(),
+            },
+        ])
+    }
+
+    /// Split the transcript in two parts holding about half of the messages each, in
+    /// order. `None` when there is a single message left.
+    pub fn halves(&self) -> Option<(Transcript, Transcript)> {
+        let count = self.message_count();
+        if count < 2 {
+            return None;
+        }
+        let empty = Transcript {
+            messages: Vec::new(),
+            threads: Vec::new(),
+            ..self.clone()
+        };
+        let (mut first, mut second) = (empty.clone(), empty);
+
+        let mut remaining = count / 2;
+        let taken = remaining.min(self.messages.len());
+        first.messages = self.messages[..taken].to_vec();
+        second.messages = self.messages[taken..].to_vec();
+        remaining -= taken;
+
+        for thread in &self.threads {
+            let taken = remaining.min(thread.messages.len());
+            // Threads without messages stay with the half they come up in
+            let in_first = taken > 0 || (thread.messages.is_empty() && remaining > 0);
+            let in_second = taken < thread.messages.len() || !in_first;
+            remaining -= taken;
+            if in_first {
+                first.threads.push(ThreadTranscript {
+                    messages: thread.messages[..taken].to_vec(),
+                    ..thread.clone()
+                });
+            }
+            if in_second {
+                second.threads.push(ThreadTranscript {
+                    messages: thread.messages[taken..].to_vec(),
+                    ..thread.clone()
+                });
+            }
+        }
+        Some((first, second))
+    }
+}
+
+fn push_messages(lines: &mut Vec<String>, messages: &[TranscriptMessage]) {
+    if messages.is_empty() {
+        lines.push(String::new());
+        lines.push("_No messages_".to_string());
+    }
+    for message in messages {
+        lines.push(String::new());
+        lines.push(format!(
+            "**{}** (<@{}>) at {}",
+            message.author_name, message.author_id, message.timestamp
+        ));
+        if !message.content.is_empty() {
+            lines.extend(message.content.lines().map(|l| format!("> {}", l)));
+        }
+        for embed in &message.embeds {
+            lines.push(format!("> [embed] {}", embed.replace('\n', " ")));
+        }
+        for attachment in &message.attachments {
+            lines.push(format!(
+                "- Attachment: [{}]({}) ({} bytes)",
+                attachment.filename, attachment.url, attachment.size
+            ));
+        }
+    }
+}
+
+/// Read the whole history of a channel and of its threads
+pub async fn export_transcript(ctx: &dyn Backend, channel_id: ChannelId) -> Result<Transcript> {
+    let channel = ctx.channel(channel_id).await?;
+    // Forum posts are threads, the forum itself has no messages
+    let messages = if channel.kind == ChannelType::Forum {
+        Vec::new()
+    } else {
+        ctx.history(channel_id).await?
+    };
+
+    let mut threads = Vec::new();
+    for thread in ctx.threads(channel_id).await? {
+        let messages = ctx.history(thread.id).await?;
+        threads.push(ThreadTranscript {
+            thread_id: thread.id.get(),
+            name: thread.name,
+            messages: messages.into_iter().map(TranscriptMessage::from).collect(),
+        });
+    }
+
+    Ok(Transcript {
+        channel_id: channel_id.get(),
+        name: channel.name,
+        topic: channel.topic,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        messages: messages.into_iter().map(TranscriptMessage::from).collect(),
+        threads,
+        part: None,
+    })
+}
+
+/// Upload a transcript to `channel_id` with a one line summary. A transcript larger
+/// than Discord accepts is split in numbered parts sent one message each, it fails only
+/// when a single message does not fit.
+pub async fn upload_transcript(
+    ctx: &dyn Backend,
+    transcript: &Transcript,
+    channel_id: ChannelId,
+) -> Result<()> {
+    let (first, second) = match send_transcript(ctx, transcript, channel_id).await {
+        Err(e) if is_too_large(&e) => transcript.halves().ok_or(e)?,
+        result => return result,
+    };
+
+    // Parts still to send, the next one last
+    let mut pending = vec![second, first];
+    let mut sent = 0;
+    while let Some(mut part) = pending.pop() {
+        part.part = Some(sent + 1);
+        match send_transcript(ctx, &part, channel_id).await {
+            Ok(()) => sent += 1,
+            Err(e) if is_too_large(&e) => {
+                let (first, second) = part.halves().ok_or(e)?;
+                pending.push(second);
+                pending.push(first);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+async fn send_transcript(
+    ctx: &dyn Backend,
+    transcript: &Transcript,
+    channel_id: ChannelId,
+) -> Result<()> {
+    ctx.send_files(channel_id, &transcript.summary(), transcript.files()?)
+        .await?;
+    Ok(())
+}
+
+/// Export a channel's transcript and upload it to `log_channel_id`, done right before
+/// the channel is deleted
+pub async fn save_transcript(
+    ctx: &dyn Backend,
+    channel_id: ChannelId,
+    log_channel_id: ChannelId,
+) -> Result<()> {
+    let transcript = export_transcript(ctx, channel_id).await?;
+    upload_transcript(ctx, &transcript, log_channel_id).await
+}
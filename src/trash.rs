@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serenity::all::{ChannelId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::backend::{is_not_found, Backend, ChannelEdit};
+use crate::config::{GuildConfig, SharedConfig};
+use crate::embed::{send_log_embed, EmbedColor};
+use crate::registry::{ChannelRecord, ChannelRegistry, TrashEntry};
+use crate::transcript::save_transcript;
+
+/// How often trashed channels are checked for purging
+const PURGE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Move a channel to the trash category, taking the category's permissions so it is
+/// hidden. Returns when it will be purged.
+pub async fn move_to_trash(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    channel_id: ChannelId,
+    by: UserId,
+) -> Result<DateTime<Utc>> {
+    let trash_category = config
+        .trash_category_id()
+        .context("No trash category configured")?;
+    let channel = ctx.channel(channel_id).await?;
+    let category = ctx.channel(trash_category).await?;
+
+    ctx.edit_channel(
+        channel_id,
+        ChannelEdit {
+            parent_id: Some(trash_category),
+            permission_overwrites: Some(category.permission_overwrites),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let now = Utc::now();
+    let purge_at = now + ChronoDuration::days(config.trash_retention_days().into());
+    registry
+        .record_trashed(
+            channel_id.get(),
+            TrashEntry {
+                trashed_at: now,
+                trashed_by: by.get(),
+                purge_at,
+                permission_overwrites: channel.permission_overwrites,
+                purge_error: None,
+            },
+        )
+        .await?;
+
+    Ok(purge_at)
+}
+
+/// Delete the trashed channels of a server whose grace period ended by `now`, logging
+/// each one. A channel that cannot be purged is reported and tried again on the next
+/// run without holding up the others. Returns the ids of the purged channels.
+pub async fn purge_expired(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    now: DateTime<Utc>,
+) -> Vec<u64> {
+    let mut purged = Vec::new();
+    for record in registry.due_purges(config.guild_id, now).await {
+        match purge_channel(ctx, config, registry, &record).await {
+            Ok(()) => purged.push(record.channel_id),
+            Err(e) => eprintln!(
+                "Could not purge channel {} of server {}: {:?}",
+                record.channel_id, config.guild_id, e
+            ),
+        }
+    }
+    purged
+}
+
+/// Save the transcript of a trashed channel, delete it and log it. A channel whose
+/// transcript cannot be saved is kept and stays due, the log channel is told the first
+/// time.
+async fn purge_channel(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    record: &ChannelRecord,
+) -> Result<()> {
+    let channel_id = ChannelId::new(record.channel_id);
+    let name = record.name().unwrap_or("unknown");
+
+    // Already gone when someone deleted it by hand. Other errors may be temporary,
+    // the record stays due and is tried again on the next run.
+    match ctx.channel(channel_id).await {
+        Ok(_) => {
+            if let Err(e) = save_transcript(ctx, channel_id, config.log_channel_id()).await {
+                let already_reported = record
+                    .trash
+                    .as_ref()
+                    .is_some_and(|t| t.purge_error.is_some());
+                if !already_reported {
+                    registry
+                        .record_purge_failed(record.channel_id, e.to_string())
+                        .await?;
+                    log_transcript_error(ctx, config, name, &e).await?;
+                }
+                return Err(e.context("The transcript could not be saved"));
+            }
+            ctx.delete_channel(channel_id).await?;
+        }
+        Err(e) if is_not_found(&e) => {}
+        Err(e) => return Err(e),
+    }
+    registry.record_deleted(record.channel_id).await?;
+
+    let deleted_by = record.trash.as_ref().map(|t| t.trashed_by).unwrap_or(0);
+    send_log_embed(
+        ctx,
+        config.log_channel_id(),
+        &format!(
+            "[{}] purged from the trash, deleted by <@{}>",
+            name, deleted_by
+        ),
+        Some(EmbedColor::Red.value()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Tell the log channel a trashed channel is kept because its transcript could not be
+/// saved
+async fn log_transcript_error(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    name: &str,
+    error: &anyhow::Error,
+) -> Result<()> {
+    send_log_embed(
+        ctx,
+        config.log_channel_id(),
+        &format!(
+            "The transcript of [{}] could not be saved, it stays in the trash until it can: {}",
+            name, error
+        ),
+        Some(EmbedColor::Red.value()),
+    )
+    .await
+}
+
+/// Purge expired trashed channels of every server, periodically. The schedule lives in
+/// the registry, so channels due while the bot was offline are purged on the first run.
+pub async fn watch_trash(
+    ctx: Arc<dyn Backend>,
+    shared: Arc<SharedConfig>,
+    registry: Arc<ChannelRegistry>,
+) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = shared.current();
+        for guild in config.guilds.values() {
+            let purged = purge_expired(ctx.as_ref(), guild, &registry, Utc::now()).await;
+            if !purged.is_empty() {
+                println!(
+                    "Purged {} channel(s) from the trash of server {}",
+                    purged.len(),
+                    guild.guild_id
+                );
+            }
+        }
+    }
+}
@@ -1,162 +1,622 @@
 use anyhow::Result;
 use serenity::all::{
-    ChannelType, Context, GuildChannel, Member, PermissionOverwrite,
-    PermissionOverwriteType, Permissions, UserId,
+    ChannelId, ChannelType, GuildId, MessageId, PermissionOverwrite, PermissionOverwriteType,
+    Permissions, RoleId, UserId,
 };
 
-use crate::{config::Config, embed::send_list_embed};
+use crate::backend::{is_not_found, Backend, ChannelData, MemberData, NewChannel, RoleData};
+use crate::config::{GuildConfig, ListStyle, PermissionTemplate, PrivateListing};
+use crate::embed::{
+    compact_pages, edit_list_message, per_channel_pages, send_list_message, ListPage,
+};
+use crate::registry::{ChannelRecord, ChannelRegistry, ListSlot};
 
 // Structure to hold channel data read from Discord
 #[derive(Debug, Clone)]
 pub struct ChannelInfo {
     pub channel_id: u64,
     pub name: String,
-    pub creator_id: u64,
+    pub kind: ChannelType,
+    /// Owner recorded in the registry, guessed from the overwrites until then
+    pub owner_id: u64,
+    /// Co-owners recorded in the registry, guessed along with the owner until then
+    pub co_owner_ids: Vec<u64>,
+    /// Channel topic, or the description kept in the registry for channels without one
     pub description: Option<String>,
+    /// Hidden from @everyone
+    pub private: bool,
+}
+
+/// Kinds of channels the bot creates and manages
+pub const MANAGED_CHANNEL_TYPES: [ChannelType; 4] = [
+    ChannelType::Text,
+    ChannelType::News,
+    ChannelType::Forum,
+    ChannelType::Voice,
+];
+
+/// Whether Discord stores a topic for channels of `kind`
+pub fn has_topic(kind: ChannelType) -> bool {
+    kind != ChannelType::Voice
 }
 
-/// Get all managed channels from Discord category
-pub async fn get_managed_channels(ctx: &Context, config: &Config) -> Result<Vec<ChannelInfo>> {
+/// Whether `kind` is a thread, including forum posts
+pub fn is_thread(kind: ChannelType) -> bool {
+    matches!(
+        kind,
+        ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+    )
+}
+
+/// Get all managed channels from Discord category, reconciled with the registry
+pub async fn get_managed_channels(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+) -> Result<Vec<ChannelInfo>> {
     let category_id = config.category_id();
     let log_channel_id = config.log_channel_id();
     let list_channel_id = config.list_channel_id();
 
-    // We need to get guild_id from one of the channels
-    let guild_id = category_id
-        .to_channel(&ctx.http)
-        .await?
-        .guild()
-        .map(|c| c.guild_id);
-
-    if guild_id.is_none() {
-        return Ok(Vec::new());
-    }
-
-    let guild_id = guild_id.unwrap();
-    let channels = guild_id.channels(&ctx.http).await?;
+    let channels = ctx.guild_channels(config.guild_id()).await?;
 
     let mut managed_channels = Vec::new();
 
-    for (channel_id, channel) in channels.iter() {
-        // Only text channels in our category, excluding log and list channels
-        if channel.kind == ChannelType::Text {
+    for channel in channels.iter() {
+        // Only managed kinds of channels in our category, excluding log and list channels
+        if MANAGED_CHANNEL_TYPES.contains(&channel.kind) {
             if let Some(parent_id) = channel.parent_id {
                 if parent_id == category_id
-                    && *channel_id != log_channel_id
-                    && *channel_id != list_channel_id
+                    && channel.id != log_channel_id
+                    && channel.id != list_channel_id
                 {
-                    // Detect creator from permission overwrites
-                    let creator_id = channel
-                        .permission_overwrites
-                        .iter()
-                        .find(|p| {
-                            matches!(p.kind, PermissionOverwriteType::Member(_))
-                                && p.allow.contains(Permissions::MANAGE_CHANNELS)
-                        })
-                        .and_then(|p| {
-                            if let PermissionOverwriteType::Member(user_id) = p.kind {
-                                Some(user_id.get())
-                            } else {
-                                None
+                    // Guess owners from permission overwrites, only used for
+                    // channels the registry does not know about yet. The owner can
+                    // manage the channel, co-owners only its messages. The bot's own
+                    // overwrite also allows managing permissions.
+                    let mut managers = Vec::new();
+                    let mut co_owner_ids = Vec::new();
+                    for p in &channel.permission_overwrites {
+                        let PermissionOverwriteType::Member(user_id) = p.kind else {
+                            continue;
+                        };
+                        if p.allow.contains(Permissions::MANAGE_CHANNELS) {
+                            if !p.allow.contains(Permissions::MANAGE_ROLES) {
+                                managers.push(user_id.get());
                             }
-                        })
-                        .unwrap_or(0);
+                        } else if p.allow.contains(Permissions::MANAGE_MESSAGES) {
+                            co_owner_ids.push(user_id.get());
+                        }
+                    }
+                    // Co-owners added before they had their own overwrite cannot be
+                    // told apart from the owner, all of them become co-owners
+                    let owner_id = match managers.as_slice() {
+                        [owner_id] => *owner_id,
+                        _ => {
+                            co_owner_ids.extend(managers);
+                            0
+                        }
+                    };
 
                     let description = channel.topic.clone();
 
                     managed_channels.push(ChannelInfo {
-                        channel_id: channel_id.get(),
+                        channel_id: channel.id.get(),
                         name: channel.name.clone(),
-                        creator_id,
+                        kind: channel.kind,
+                        owner_id,
+                        co_owner_ids,
                         description,
+                        private: is_private(channel),
                     });
                 }
             }
         }
     }
 
-    Ok(managed_channels)
+    registry.reconcile(config.guild_id, managed_channels).await
 }
 
-/// Check if a channel is managed by the bot
-pub async fn is_managed_channel(ctx: &Context, config: &Config, channel_id: u64) -> Result<bool> {
-    let channels = get_managed_channels(ctx, config).await?;
-    Ok(channels.iter().any(|c| c.channel_id == channel_id))
+/// Most channels Discord allows in a category
+pub const MAX_CATEGORY_CHANNELS: usize = 50;
+
+/// Refusal to send when the channel category cannot take another channel
+pub async fn category_full_refusal(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+) -> Result<Option<String>> {
+    let count = ctx
+        .guild_channels(config.guild_id())
+        .await?
+        .iter()
+        .filter(|c| c.parent_id == Some(config.category_id()))
+        .count();
+    if count < MAX_CATEGORY_CHANNELS {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "The channel category is full ({} channels), delete or archive a channel first!",
+        MAX_CATEGORY_CHANNELS
+    )))
 }
 
-/// Update the channel list in the list channel
-pub async fn update_channel_list(ctx: &Context, config: &Config) -> Result<()> {
-    let list_channel = config.list_channel_id();
+/// Managed channels whose primary owner is `user_id`
+pub async fn owned_channel_ids(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    user_id: u64,
+) -> Result<Vec<u64>> {
+    Ok(get_managed_channels(ctx, config, registry)
+        .await?
+        .into_iter()
+        .filter(|c| c.owner_id == user_id)
+        .map(|c| c.channel_id)
+        .collect())
+}
+
+/// A member who cannot own another channel
+pub struct QuotaReached {
+    pub owned: Vec<u64>,
+    pub limit: u32,
+}
 
-    // Delete all existing messages
-    let messages = list_channel.messages(&ctx.http, Default::default()).await?;
-    for message in messages {
-        message.delete(&ctx.http).await?;
+/// Whether `member` already owns as many channels as their roles allow. Managers are
+/// not limited.
+pub async fn quota_reached(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    user_id: u64,
+    member: &MemberData,
+) -> Result<Option<QuotaReached>> {
+    if has_manager_role(member, config).await {
+        return Ok(None);
+    }
+    let Some(limit) = config.channel_limit(&member.roles) else {
+        return Ok(None);
+    };
+    let owned = owned_channel_ids(ctx, config, registry, user_id).await?;
+    if owned.len() >= limit as usize {
+        Ok(Some(QuotaReached { owned, limit }))
+    } else {
+        Ok(None)
     }
+}
 
-    // Get all managed channels from Discord
-    let mut channels = get_managed_channels(ctx, config).await?;
+/// Check if a channel is managed by the bot
+pub async fn is_managed_channel(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    channel_id: u64,
+) -> Result<bool> {
+    let channels = get_managed_channels(ctx, config, registry).await?;
+    Ok(channels.iter().any(|c| c.channel_id == channel_id))
+}
+
+/// Render the list messages for every managed channel, sorted by name
+async fn render_channel_list(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+) -> Result<Vec<ListPage>> {
+    let mut channels = get_managed_channels(ctx, config, registry).await?;
+    if config.private_listing == PrivateListing::Hidden {
+        channels.retain(|c| !c.private);
+    }
 
     // Sort by name
     channels.sort_by(|a, b| a.name.cmp(&b.name));
 
-    // Send embed for each channel
-    for channel_info in channels {
-        send_list_embed(
-            ctx,
-            list_channel,
-            serenity::all::ChannelId::new(channel_info.channel_id),
-            channel_info.description.as_ref(),
-            channel_info.creator_id,
-        )
-        .await?;
+    Ok(match config.list_style {
+        ListStyle::PerChannel => per_channel_pages(&channels),
+        ListStyle::Compact => compact_pages(&channels),
+    })
+}
+
+/// Update the channel list in the list channel, touching only the messages that changed.
+///
+/// Messages whose channels are all gone are deleted in place, which keeps the order of
+/// the remaining messages. The remaining messages are then filled in display order and only
+/// edited when their content differs; new entries are appended at the end. Falls back to
+/// a full rebuild when nothing is tracked yet or a tracked message cannot be updated.
+pub async fn update_channel_list(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+) -> Result<()> {
+    let list_channel = config.list_channel_id();
+    let slots = registry.list_slots(list_channel.get()).await;
+
+    if slots.is_empty() {
+        return rebuild_channel_list(ctx, config, registry).await;
+    }
+
+    let pages = render_channel_list(ctx, config, registry).await?;
+
+    if let Err(e) = apply_list_changes(ctx, registry, list_channel, slots, &pages).await {
+        eprintln!("Incremental list update failed, rebuilding: {:?}", e);
+        return rebuild_channel_list(ctx, config, registry).await;
     }
 
     Ok(())
 }
 
-pub async fn has_special_role(member: &Member, config: &Config) -> bool {
+async fn apply_list_changes(
+    ctx: &dyn Backend,
+    registry: &ChannelRegistry,
+    list_channel: ChannelId,
+    slots: Vec<ListSlot>,
+    pages: &[ListPage],
+) -> Result<()> {
+    // Drop the messages whose channels no longer exist
+    let mut kept = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let still_listed = pages
+            .iter()
+            .any(|p| p.channel_ids.iter().any(|id| slot.channel_ids.contains(id)));
+        if still_listed {
+            kept.push(slot);
+        } else {
+            ctx.delete_message(list_channel, MessageId::new(slot.message_id))
+                .await?;
+        }
+    }
+
+    let mut new_slots = Vec::with_capacity(pages.len());
+    for (index, page) in pages.iter().enumerate() {
+        let message_id = match kept.get(index) {
+            Some(slot) => {
+                let message_id = MessageId::new(slot.message_id);
+                if slot.embeds != page.embeds {
+                    edit_list_message(ctx, list_channel, message_id, &page.embeds).await?;
+                }
+                message_id
+            }
+            None => send_list_message(ctx, list_channel, &page.embeds).await?,
+        };
+
+        new_slots.push(ListSlot {
+            message_id: message_id.get(),
+            channel_ids: page.channel_ids.clone(),
+            embeds: page.embeds.clone(),
+        });
+    }
+
+    // Leftover messages, e.g. when the list now needs fewer pages
+    for slot in kept.iter().skip(pages.len()) {
+        ctx.delete_message(list_channel, MessageId::new(slot.message_id))
+            .await?;
+    }
+
+    registry.set_list_slots(list_channel.get(), new_slots).await
+}
+
+/// Wipe the list channel and post the whole channel list again
+pub async fn rebuild_channel_list(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+) -> Result<()> {
+    let list_channel = config.list_channel_id();
+
+    // Delete the tracked list messages, then whatever else is left in the channel. A
+    // tracked message may already have been removed by hand.
+    for slot in registry.list_slots(list_channel.get()).await {
+        match ctx
+            .delete_message(list_channel, MessageId::new(slot.message_id))
+            .await
+        {
+            Err(e) if !is_not_found(&e) => return Err(e),
+            _ => {}
+        }
+    }
+    for message in ctx.history(list_channel).await? {
+        ctx.delete_message(list_channel, message.id).await?;
+    }
+
+    let pages = render_channel_list(ctx, config, registry).await?;
+
+    // Send a message for each page of the list
+    let mut slots = Vec::with_capacity(pages.len());
+    for page in pages {
+        let message_id = send_list_message(ctx, list_channel, &page.embeds).await?;
+        slots.push(ListSlot {
+            message_id: message_id.get(),
+            channel_ids: page.channel_ids,
+            embeds: page.embeds,
+        });
+    }
+
+    registry.set_list_slots(list_channel.get(), slots).await
+}
+
+pub async fn has_special_role(member: &MemberData, config: &GuildConfig) -> bool {
     member.roles.contains(&config.special_role_id())
 }
 
-pub async fn has_manager_role(member: &Member, config: &Config) -> bool {
+pub async fn has_manager_role(member: &MemberData, config: &GuildConfig) -> bool {
     member.roles.contains(&config.manager_role_id())
 }
 
-pub async fn create_channel_with_permissions(
-    ctx: &Context,
-    config: &Config,
-    name: &str,
-    creator_id: UserId,
-    guild_id: serenity::model::id::GuildId,
-) -> Result<GuildChannel> {
-    let everyone_role = serenity::model::id::RoleId::new(guild_id.get());
-    let category_id = config.category_id();
+/// Permissions a member has in a channel, resolved like Discord does: server-wide role
+/// permissions, then the @everyone, role and member overwrites of the channel
+pub fn channel_permissions(
+    channel: &ChannelData,
+    roles: &[RoleData],
+    user_id: UserId,
+    member_roles: &[RoleId],
+) -> Permissions {
+    let everyone_role = RoleId::new(channel.guild_id.get());
+
+    let mut permissions = roles
+        .iter()
+        .filter(|r| r.id == everyone_role || member_roles.contains(&r.id))
+        .fold(Permissions::empty(), |acc, r| acc | r.permissions);
+    if permissions.contains(Permissions::ADMINISTRATOR) {
+        return Permissions::all();
+    }
+
+    let overwrites = &channel.permission_overwrites;
+    for overwrite in overwrites {
+        if overwrite.kind == PermissionOverwriteType::Role(everyone_role) {
+            permissions = (permissions & !overwrite.deny) | overwrite.allow;
+        }
+    }
+
+    // Role overwrites are combined before being applied
+    let (mut allow, mut deny) = (Permissions::empty(), Permissions::empty());
+    for overwrite in overwrites {
+        if let PermissionOverwriteType::Role(role_id) = overwrite.kind {
+            if role_id != everyone_role && member_roles.contains(&role_id) {
+                allow |= overwrite.allow;
+                deny |= overwrite.deny;
+            }
+        }
+    }
+    permissions = (permissions & !deny) | allow;
+
+    for overwrite in overwrites {
+        if overwrite.kind == PermissionOverwriteType::Member(user_id) {
+            permissions = (permissions & !overwrite.deny) | overwrite.allow;
+        }
+    }
+
+    permissions
+}
+
+/// Overwrite giving the owner of a managed channel its permissions, those of the
+/// template's owner rule when the channel was created from one that sets it
+pub fn owner_overwrite(
+    owner_id: UserId,
+    template: Option<&PermissionTemplate>,
+) -> PermissionOverwrite {
+    let (allow, deny) = match template.and_then(|t| t.owner.as_ref()) {
+        Some(rule) => (rule.allow(), rule.deny()),
+        None => (
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::MANAGE_CHANNELS,
+            Permissions::empty(),
+        ),
+    };
+    PermissionOverwrite {
+        allow,
+        deny,
+        kind: PermissionOverwriteType::Member(owner_id),
+    }
+}
+
+/// Overwrite of a co-owner: the owner's with Manage Messages in place of Manage Channel,
+/// so the primary owner can be told apart from the overwrites alone
+pub fn co_owner_overwrite(
+    user_id: UserId,
+    template: Option<&PermissionTemplate>,
+) -> PermissionOverwrite {
+    let mut overwrite = owner_overwrite(user_id, template);
+    overwrite.allow.remove(Permissions::MANAGE_CHANNELS);
+    overwrite.allow.insert(Permissions::MANAGE_MESSAGES);
+    overwrite.deny.remove(Permissions::MANAGE_MESSAGES);
+    overwrite
+}
+
+/// Template a channel was created from, if the server still has it
+pub fn record_template<'a>(
+    config: &'a GuildConfig,
+    record: &ChannelRecord,
+) -> Option<&'a PermissionTemplate> {
+    record
+        .template
+        .as_deref()
+        .and_then(|name| config.template(name))
+}
+
+/// Overwrite letting a contributor of a managed channel post in it
+pub fn contributor_overwrite(kind: PermissionOverwriteType) -> PermissionOverwrite {
+    PermissionOverwrite {
+        allow: Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+        deny: Permissions::empty(),
+        kind,
+    }
+}
+
+/// Overwrites of a managed channel with those of its owners, contributors and invited
+/// members rebuilt from its record, keeping every other overwrite. An owner who is also a
+/// contributor only gets the owner or co-owner overwrite, invited members get the
+/// contributor one.
+pub fn record_overwrites(
+    overwrites: Vec<PermissionOverwrite>,
+    record: &ChannelRecord,
+    config: &GuildConfig,
+) -> Vec<PermissionOverwrite> {
+    let owners: Vec<UserId> = record.owner_ids().into_iter().map(UserId::new).collect();
+    let is_owner = |kind: &PermissionOverwriteType| matches!(kind, PermissionOverwriteType::Member(user_id) if owners.contains(user_id));
+    let mut contributors: Vec<PermissionOverwriteType> = Vec::new();
+    let invited = record
+        .invited
+        .iter()
+        .map(|&id| PermissionOverwriteType::Member(UserId::new(id)));
+    for kind in record
+        .contributors
+        .iter()
+        .map(|c| c.overwrite_kind())
+        .chain(invited)
+    {
+        if !is_owner(&kind) && !contributors.contains(&kind) {
+            contributors.push(kind);
+        }
+    }
 
-    let permissions = vec![
-        PermissionOverwrite {
-            allow: Permissions::VIEW_CHANNEL
+    let mut overwrites: Vec<_> = overwrites
+        .into_iter()
+        .filter(|o| !is_owner(&o.kind) && !contributors.contains(&o.kind))
+        .collect();
+    let template = record_template(config, record);
+    overwrites.extend(owners.iter().map(|&user_id| {
+        if user_id.get() == record.owner_id() {
+            owner_overwrite(user_id, template)
+        } else {
+            co_owner_overwrite(user_id, template)
+        }
+    }));
+    overwrites.extend(contributors.into_iter().map(contributor_overwrite));
+    overwrites
+}
+
+/// Default @everyone permissions of a new channel of `kind`: forums and voice channels are
+/// open to everyone, members can only read text and announcement channels and open
+/// threads in them
+fn everyone_defaults(kind: ChannelType) -> (Permissions, Permissions) {
+    match kind {
+        ChannelType::Forum => (
+            Permissions::VIEW_CHANNEL
                 | Permissions::SEND_MESSAGES
-                | Permissions::MANAGE_CHANNELS,
-            deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Member(creator_id),
-        },
-        PermissionOverwrite {
-            allow: Permissions::VIEW_CHANNEL | Permissions::CREATE_PUBLIC_THREADS,
-            deny: Permissions::SEND_MESSAGES,
+                | Permissions::SEND_MESSAGES_IN_THREADS,
+            Permissions::empty(),
+        ),
+        ChannelType::Voice => (
+            Permissions::VIEW_CHANNEL | Permissions::CONNECT | Permissions::SPEAK,
+            Permissions::SEND_MESSAGES,
+        ),
+        _ => (
+            Permissions::VIEW_CHANNEL | Permissions::CREATE_PUBLIC_THREADS,
+            Permissions::SEND_MESSAGES,
+        ),
+    }
+}
+
+/// How a new channel is set up
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelSettings<'a> {
+    pub kind: ChannelType,
+    pub template: Option<&'a PermissionTemplate>,
+    /// Hidden from @everyone, only owners and invited members see it
+    pub private: bool,
+}
+
+/// Whether @everyone is denied the view of `channel`
+pub fn is_private(channel: &ChannelData) -> bool {
+    let everyone_role = RoleId::new(channel.guild_id.get());
+    channel.permission_overwrites.iter().any(|o| {
+        o.kind == PermissionOverwriteType::Role(everyone_role)
+            && o.deny.contains(Permissions::VIEW_CHANNEL)
+    })
+}
+
+/// Overwrite keeping the bot in a channel hidden from @everyone, with what it needs to
+/// manage the channel and export its transcript
+pub fn bot_overwrite(bot_user_id: UserId) -> PermissionOverwrite {
+    PermissionOverwrite {
+        allow: Permissions::VIEW_CHANNEL
+            | Permissions::SEND_MESSAGES
+            | Permissions::READ_MESSAGE_HISTORY
+            | Permissions::MANAGE_CHANNELS
+            | Permissions::MANAGE_ROLES,
+        deny: Permissions::empty(),
+        kind: PermissionOverwriteType::Member(bot_user_id),
+    }
+}
+
+/// Overwrites of a new channel: the default owner and @everyone overwrites for its kind,
+/// each replaced by the template's rule when it sets one, followed by the template's role
+/// overwrites. A private channel's @everyone overwrite also denies the view, and a
+/// channel hidden from @everyone gets an overwrite for the bot.
+pub fn channel_overwrites(
+    owner_id: UserId,
+    bot_user_id: UserId,
+    guild_id: GuildId,
+    settings: ChannelSettings<'_>,
+) -> Vec<PermissionOverwrite> {
+    let ChannelSettings {
+        kind,
+        template,
+        private,
+    } = settings;
+    let everyone_role = RoleId::new(guild_id.get());
+
+    let owner = owner_overwrite(owner_id, template);
+    let mut everyone = match template.and_then(|t| t.everyone.as_ref()) {
+        Some(rule) => PermissionOverwrite {
+            allow: rule.allow(),
+            deny: rule.deny(),
             kind: PermissionOverwriteType::Role(everyone_role),
         },
-    ];
+        None => {
+            let (allow, deny) = everyone_defaults(kind);
+            PermissionOverwrite {
+                allow,
+                deny,
+                kind: PermissionOverwriteType::Role(everyone_role),
+            }
+        }
+    };
+
+    if private {
+        everyone.allow -= Permissions::VIEW_CHANNEL;
+        everyone.deny |= Permissions::VIEW_CHANNEL;
+    }
+
+    let hidden = everyone.deny.contains(Permissions::VIEW_CHANNEL);
+    let mut permissions = vec![owner, everyone];
+    if hidden {
+        permissions.push(bot_overwrite(bot_user_id));
+    }
+    if let Some(template) = template {
+        permissions.extend(template.roles.iter().map(|rule| PermissionOverwrite {
+            allow: rule.allow(),
+            deny: rule.deny(),
+            kind: PermissionOverwriteType::Role(rule.role_id()),
+        }));
+    }
+    permissions
+}
 
-    let channel = guild_id
+pub async fn create_channel_with_permissions(
+    ctx: &dyn Backend,
+    config: &GuildConfig,
+    name: &str,
+    creator_id: UserId,
+    bot_user_id: UserId,
+    guild_id: GuildId,
+    settings: ChannelSettings<'_>,
+) -> Result<ChannelData> {
+    let category_id = config.category_id();
+
+    let channel = ctx
         .create_channel(
-            &ctx.http,
-            serenity::all::CreateChannel::new(name)
-                .kind(serenity::all::ChannelType::Text)
-                .category(category_id)
-                .permissions(permissions),
+            guild_id,
+            NewChannel {
+                name: name.to_string(),
+                kind: settings.kind,
+                parent_id: Some(category_id),
+                permission_overwrites: channel_overwrites(
+                    creator_id,
+                    bot_user_id,
+                    guild_id,
+                    settings,
+                ),
+            },
         )
         .await?;
 
     Ok(channel)
-}
\ No newline at end of file
+}
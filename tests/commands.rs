@@ -0,0 +1,1702 @@
+mod common;
+
+use common::{
+    dispatch, request_in, temp_path, BotChannels, RequestBuilder, TestGuild, CREATOR, GUILD_ID,
+    MANAGER, OTHER_MEMBER, SPECIAL_ROLE_ID,
+};
+use discord_channel_bot::backend::fake::BOT_USER_ID;
+use discord_channel_bot::backend::{Backend, FakeGuild};
+use discord_channel_bot::commands::CommandSet;
+use discord_channel_bot::config::{Config, SharedConfig};
+use discord_channel_bot::registry::{ChannelRegistry, ChannelStatus};
+use discord_channel_bot::utils::{channel_permissions, get_managed_channels};
+use serenity::all::{
+    ChannelId, ChannelType, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId,
+    UserId,
+};
+
+async fn create_channel(test: &TestGuild, user_id: u64, name: &str) -> ChannelId {
+    create_with(
+        test,
+        test.command("create", user_id, test.list_channel_id)
+            .string("name", name),
+    )
+    .await
+}
+
+/// Run a /create request and return the new channel
+async fn create_with(test: &TestGuild, request: RequestBuilder) -> ChannelId {
+    let request = request.build();
+    let name = request.string_option("name").unwrap().to_string();
+    test.run(&request).await.unwrap();
+    test.guild
+        .channel_named(&name)
+        .expect("channel was not created")
+        .id
+}
+
+#[tokio::test]
+async fn create_makes_channel_owned_by_creator() {
+    let test = TestGuild::new();
+
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    let channel = test.guild.channel_data(channel_id).unwrap();
+    assert_eq!(channel.parent_id, Some(test.category_id));
+    let creator_overwrite = channel
+        .permission_overwrites
+        .iter()
+        .find(|p| p.kind == PermissionOverwriteType::Member(UserId::new(CREATOR)))
+        .unwrap();
+    assert!(creator_overwrite
+        .allow
+        .contains(Permissions::MANAGE_CHANNELS));
+
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", channel_id, CREATOR)]
+    );
+    assert_eq!(
+        test.log_entries(),
+        vec![format!("<#{}> created by <@{}>", channel_id, CREATOR)]
+    );
+    assert_eq!(
+        test.last_response(),
+        format!("Channel created successfully! <#{}>", channel_id)
+    );
+}
+
+#[tokio::test]
+async fn create_requires_special_role() {
+    let test = TestGuild::new();
+    let request = test.request("create", 999, test.list_channel_id, &[("name", "nope")]);
+
+    test.run(&request).await.unwrap();
+
+    assert!(test.guild.channel_named("nope").is_none());
+    assert_eq!(
+        test.last_response(),
+        "You do not have permission to use this command!"
+    );
+}
+
+#[tokio::test]
+async fn creator_survives_hand_edited_overwrites() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    // A moderator replacing the overwrites must not change the recorded owner
+    test.guild.set_permission_overwrites(channel_id, Vec::new());
+    let request = test.request("updatelist", MANAGER, test.list_channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", channel_id, CREATOR)]
+    );
+}
+
+#[tokio::test]
+async fn rename_updates_channel_and_history() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    let request = test.request("rename", CREATOR, channel_id, &[("name", "project-beta")]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.guild.channel_data(channel_id).unwrap().name,
+        "project-beta"
+    );
+    let record = test.registry.get(channel_id.get()).await.unwrap();
+    assert_eq!(record.name(), Some("project-beta"));
+    assert_eq!(record.name_history.len(), 2);
+    assert_eq!(test.last_response(), "Channel renamed successfully!");
+}
+
+#[tokio::test]
+async fn rename_refuses_unmanaged_channel() {
+    let test = TestGuild::new();
+
+    let request = test.request(
+        "rename",
+        CREATOR,
+        test.log_channel_id,
+        &[("name", "hijack")],
+    );
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.guild.channel_data(test.log_channel_id).unwrap().name,
+        "log"
+    );
+    assert_eq!(
+        test.last_response(),
+        "This channel was not created by the bot and cannot be used with /rename!"
+    );
+}
+
+#[tokio::test]
+async fn only_owner_can_change_a_channel() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    for (name, options) in [
+        ("rename", vec![("name", "hijack")]),
+        ("description", vec![("text", "hijack")]),
+        ("delete", vec![]),
+    ] {
+        let request = test.request(name, OTHER_MEMBER, channel_id, &options);
+        test.run(&request).await.unwrap();
+
+        assert_eq!(
+            test.last_response(),
+            format!(
+                "This channel belongs to <@{}>, only they or a manager can use /{} here!",
+                CREATOR, name
+            )
+        );
+    }
+
+    let channel = test.guild.channel_data(channel_id).unwrap();
+    assert_eq!(channel.name, "project-alpha");
+    assert_eq!(channel.topic, None);
+}
+
+#[tokio::test]
+async fn manager_can_change_any_channel() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    let request = test.request("rename", MANAGER, channel_id, &[("name", "project-beta")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.guild.channel_data(channel_id).unwrap().name,
+        "project-beta"
+    );
+
+    let request = test.request("delete", MANAGER, channel_id, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), MANAGER, channel_id);
+    test.run(&request).await.unwrap();
+    assert!(test.guild.channel_data(channel_id).is_none());
+}
+
+#[tokio::test]
+async fn create_normalizes_and_refuses_duplicates() {
+    let test = TestGuild::new();
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "Project Alpha!")],
+    );
+    test.run(&request).await.unwrap();
+    let alpha = test.guild.channel_named("project-alpha").unwrap().id;
+    assert_eq!(
+        test.guild.responses()[0],
+        "Creating channel #project-alpha (adjusted from `Project Alpha!`)..."
+    );
+
+    test.guild.reset_write_calls();
+    for (input, expected) in [
+        (
+            "project alpha",
+            format!("A channel with this name already exists: <#{}>", alpha),
+        ),
+        (
+            "LIST",
+            format!(
+                "This name is used by the bot's own channel <#{}>!",
+                test.list_channel_id
+            ),
+        ),
+    ] {
+        let request = test.request(
+            "create",
+            OTHER_MEMBER,
+            test.list_channel_id,
+            &[("name", input)],
+        );
+        test.run(&request).await.unwrap();
+        assert_eq!(test.last_response(), expected);
+    }
+    assert_eq!(test.guild.write_calls(), 0);
+}
+
+#[tokio::test]
+async fn rename_refuses_another_channels_name() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    let request = test.request("rename", CREATOR, beta, &[("name", "Alpha")]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.last_response(),
+        format!("A channel with this name already exists: <#{}>", alpha)
+    );
+    assert_eq!(test.guild.channel_data(beta).unwrap().name, "beta");
+}
+
+#[tokio::test]
+async fn naming_rules_apply_to_members_but_not_managers() {
+    let test = TestGuild::with_config(&format!(
+        "[guilds.{}.naming]\nrequired_prefix = \"proj-\"\nblocked_words = [\"admin\"]",
+        GUILD_ID
+    ));
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "alpha")],
+    );
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "Channel names must start with `proj-`!"
+    );
+    assert!(test.guild.channel_named("alpha").is_none());
+
+    let channel_id = create_channel(&test, CREATOR, "proj-alpha").await;
+    let request = test.request("rename", CREATOR, channel_id, &[("name", "proj-admin")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "Channel name contains the blocked word `admin`!"
+    );
+
+    create_channel(&test, MANAGER, "admin-notes").await;
+}
+
+#[tokio::test]
+async fn quota_refuses_and_lists_owned_channels() {
+    let test = TestGuild::with_config("max_channels_per_user = 1");
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("create", CREATOR, test.list_channel_id, &[("name", "beta")]);
+    test.run(&request).await.unwrap();
+
+    assert!(test.guild.channel_named("beta").is_none());
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "You already own 1 channel(s), the limit is 1: <#{}>. Delete one before creating another.",
+            alpha
+        )
+    );
+
+    // Channels of other members do not count
+    create_channel(&test, OTHER_MEMBER, "gamma").await;
+}
+
+#[tokio::test]
+async fn role_limit_overrides_default_quota() {
+    let test = TestGuild::with_config(&format!(
+        "max_channels_per_user = 1\n[[guilds.{}.role_channel_limits]]\nrole_id = {}\nmax_channels = 2",
+        GUILD_ID, SPECIAL_ROLE_ID
+    ));
+
+    create_channel(&test, CREATOR, "alpha").await;
+    create_channel(&test, CREATOR, "beta").await;
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "gamma")],
+    );
+    test.run(&request).await.unwrap();
+    assert!(test.guild.channel_named("gamma").is_none());
+}
+
+#[tokio::test]
+async fn managers_bypass_the_quota() {
+    let test = TestGuild::with_config("max_channels_per_user = 0");
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "alpha")],
+    );
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "You are not allowed to create channels!"
+    );
+
+    create_channel(&test, MANAGER, "beta").await;
+}
+
+#[tokio::test]
+async fn role_check_runs_before_channel_check() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+    test.guild.reset_write_calls();
+
+    // No special role: refused before anything is looked up or changed
+    let request = test.request("delete", 999, channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.last_response(),
+        "You do not have permission to use this command!"
+    );
+    assert!(test.guild.channel_data(channel_id).is_some());
+    assert_eq!(test.guild.write_calls(), 0);
+}
+
+#[test]
+fn every_command_is_registered_once() {
+    let set = CommandSet::new();
+    let names = set.names();
+
+    let mut unique = names.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), names.len());
+    assert_eq!(set.register_all().len(), names.len());
+    for name in names {
+        assert_eq!(set.get(name).unwrap().name(), name);
+    }
+}
+
+#[tokio::test]
+async fn description_sets_and_clears_topic() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "project-alpha").await;
+
+    let request = test.request("description", CREATOR, channel_id, &[("text", "Roadmap")]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.guild
+            .channel_data(channel_id)
+            .unwrap()
+            .topic
+            .as_deref(),
+        Some("Roadmap")
+    );
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> - Roadmap by <@{}>", channel_id, CREATOR)]
+    );
+
+    let request = test.request("description", CREATOR, channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(test.guild.channel_data(channel_id).unwrap().topic, None);
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", channel_id, CREATOR)]
+    );
+}
+
+#[tokio::test]
+async fn delete_removes_channel_and_list_entry() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, OTHER_MEMBER, "beta").await;
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, alpha);
+    test.run(&request).await.unwrap();
+
+    assert!(test.guild.channel_data(alpha).is_none());
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", beta, OTHER_MEMBER)]
+    );
+    // The transcript is posted next to the log entry
+    let files = test.guild.files(test.log_channel_id);
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, format!("transcript-alpha-{}.json", alpha));
+    assert!(test
+        .log_entries()
+        .contains(&format!("[alpha] deleted by <@{}>\n", CREATOR)));
+}
+
+#[tokio::test]
+async fn delete_keeps_the_channel_when_the_transcript_cannot_be_saved() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    test.guild.set_upload_limit(0);
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, alpha);
+    test.run(&request).await.unwrap();
+
+    assert!(test.guild.channel_data(alpha).is_some());
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().status,
+        ChannelStatus::Active
+    );
+    assert_eq!(
+        test.last_response(),
+        "The transcript of this channel could not be saved, so it was not deleted: Request entity too large"
+    );
+    assert!(!test
+        .log_entries()
+        .contains(&format!("[alpha] deleted by <@{}>\n", CREATOR)));
+}
+
+#[tokio::test]
+async fn updatelist_requires_manager() {
+    let test = TestGuild::new();
+
+    let request = test.request("updatelist", CREATOR, test.list_channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(test.last_response(), "Only managers can use this command!");
+    assert!(test.log_entries().is_empty());
+}
+
+#[tokio::test]
+async fn updatelist_clears_the_whole_list_channel() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    // More than one page of history
+    test.guild
+        .add_messages(test.list_channel_id, OTHER_MEMBER, 120);
+
+    let request = test.request("updatelist", MANAGER, test.list_channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    let messages = test.guild.channel_messages(test.list_channel_id);
+    assert!(messages.iter().all(|m| m.author_id.get() == BOT_USER_ID));
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", alpha, CREATOR)]
+    );
+}
+
+#[tokio::test]
+async fn list_update_only_touches_changed_entries() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    create_channel(&test, CREATOR, "beta").await;
+    create_channel(&test, CREATOR, "gamma").await;
+
+    test.guild.reset_write_calls();
+    let request = test.request("description", CREATOR, alpha, &[("text", "First")]);
+    test.run(&request).await.unwrap();
+
+    // Topic edit, one list message edit and one log message
+    assert_eq!(test.guild.write_calls(), 3);
+    assert_eq!(test.list_entries().len(), 3);
+}
+
+#[tokio::test]
+async fn compact_list_packs_channels_in_one_message() {
+    let test = TestGuild::with_config(r#"list_style = "compact""#);
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    assert_eq!(test.guild.channel_messages(test.list_channel_id).len(), 1);
+    assert_eq!(
+        test.list_entries(),
+        vec![format!(
+            "**A**\n<#{}> by <@{}>\n\n**B**\n<#{}> by <@{}>",
+            alpha, CREATOR, beta, CREATOR
+        )]
+    );
+}
+
+#[tokio::test]
+async fn guilds_keep_separate_lists_and_logs() {
+    let first = FakeGuild::new(GUILD_ID);
+    let second = FakeGuild::new(GUILD_ID + 1);
+    let first_channels = BotChannels::add_to(&first);
+    let second_channels = BotChannels::add_to(&second);
+    let registry_path = temp_path("json");
+    let config = Config::parse(&format!(
+        "token = \"test\"\n{}{}",
+        first_channels.guild_table(GUILD_ID, ""),
+        second_channels.guild_table(GUILD_ID + 1, "")
+    ))
+    .unwrap();
+    let registry = ChannelRegistry::load(&registry_path).unwrap();
+    let shared = SharedConfig::new(temp_path("toml").to_str().unwrap(), config.clone());
+
+    for (guild, channels, name) in [
+        (&first, &first_channels, "alpha"),
+        (&second, &second_channels, "beta"),
+    ] {
+        let request = request_in(
+            guild,
+            "create",
+            CREATOR,
+            channels.list_channel_id,
+            &[("name", name)],
+        );
+        let guild_config = config.guild(guild.guild_id()).unwrap();
+        dispatch(guild, guild_config, &registry, &shared, &request)
+            .await
+            .unwrap();
+    }
+
+    // Rebuilding the first list must not treat the second guild's channel as deleted
+    let request = request_in(
+        &first,
+        "updatelist",
+        MANAGER,
+        first_channels.list_channel_id,
+        &[],
+    );
+    dispatch(
+        &first,
+        config.guild(first.guild_id()).unwrap(),
+        &registry,
+        &shared,
+        &request,
+    )
+    .await
+    .unwrap();
+
+    let alpha = first.channel_named("alpha").unwrap().id;
+    let beta = second.channel_named("beta").unwrap().id;
+    assert_eq!(
+        registry.get(beta.get()).await.unwrap().status,
+        ChannelStatus::Active
+    );
+    for (guild, channels, channel_id, log_count) in [
+        (&first, &first_channels, alpha, 2),
+        (&second, &second_channels, beta, 1),
+    ] {
+        let entries: Vec<String> = guild
+            .channel_messages(channels.list_channel_id)
+            .into_iter()
+            .flat_map(|m| m.embeds)
+            .map(|e| e.description)
+            .collect();
+        assert_eq!(
+            entries,
+            vec![format!("<#{}> by <@{}>", channel_id, CREATOR)]
+        );
+        assert_eq!(
+            guild.channel_messages(channels.log_channel_id).len(),
+            log_count
+        );
+    }
+
+    let _ = std::fs::remove_file(registry_path);
+}
+
+#[tokio::test]
+async fn delete_asks_for_confirmation() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "alpha").await;
+    test.guild.add_messages(channel_id, OTHER_MEMBER, 3);
+
+    let request = test.request("delete", CREATOR, channel_id, &[]);
+    test.run(&request).await.unwrap();
+
+    // The reply is sent before counting, the buttons are added to it afterwards
+    let responses = test.guild.responses();
+    assert_eq!(responses[responses.len() - 2], "Checking the channel...");
+    assert!(test.last_response().starts_with(&format!(
+        "Delete **#alpha**? It has 3 message(s) and was created by <@{}>.",
+        CREATOR
+    )));
+    let labels: Vec<String> = test.guild.buttons().into_iter().map(|b| b.label).collect();
+    assert_eq!(labels, vec!["Delete", "Cancel"]);
+    assert!(test.guild.channel_data(channel_id).is_some());
+}
+
+#[tokio::test]
+async fn delete_can_be_cancelled() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("delete", CREATOR, channel_id, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Cancel"), CREATOR, channel_id);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(test.last_response(), "Deletion cancelled.");
+    assert!(test.guild.buttons().is_empty());
+    assert!(test.guild.channel_data(channel_id).is_some());
+}
+
+#[tokio::test]
+async fn delete_confirmation_expires_and_stays_in_its_channel() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    let expired = format!("delete:confirm:{}:{}", alpha, 1);
+    let request = test.click(&expired, CREATOR, alpha);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "This confirmation has expired, run /delete again."
+    );
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, beta);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "This confirmation is not for this channel."
+    );
+
+    assert!(test.guild.channel_data(alpha).is_some());
+    assert!(test.guild.channel_data(beta).is_some());
+}
+
+#[tokio::test]
+async fn delete_confirmation_is_checked_like_the_command() {
+    let test = TestGuild::new();
+    let channel_id = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("delete", CREATOR, channel_id, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), OTHER_MEMBER, channel_id);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "This channel belongs to <@{}>, only they or a manager can use /delete here!",
+            CREATOR
+        )
+    );
+    assert!(test.guild.channel_data(channel_id).is_some());
+}
+
+#[tokio::test]
+async fn archive_freezes_channel_and_removes_it_from_list() {
+    let mut test = TestGuild::new();
+    let archive_id = test.enable_archive(false);
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, OTHER_MEMBER, "beta").await;
+
+    let request = test.request("archive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+
+    let channel = test.guild.channel_data(alpha).unwrap();
+    assert_eq!(channel.name, "archived-alpha");
+    assert_eq!(channel.parent_id, Some(archive_id));
+    let owner = channel
+        .permission_overwrites
+        .iter()
+        .find(|o| o.kind == PermissionOverwriteType::Member(UserId::new(CREATOR)))
+        .unwrap();
+    assert!(owner.deny.contains(Permissions::SEND_MESSAGES));
+    assert!(!owner.allow.contains(Permissions::SEND_MESSAGES));
+
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", beta, OTHER_MEMBER)]
+    );
+    assert!(test
+        .log_entries()
+        .contains(&format!("[archived-alpha] archived by <@{}>\n", CREATOR)));
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.status, ChannelStatus::Archived);
+    assert_eq!(record.name(), Some("archived-alpha"));
+
+    // Refreshing the list keeps the archived record
+    let request = test.request("updatelist", MANAGER, test.list_channel_id, &[]);
+    test.run(&request).await.unwrap();
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.status, ChannelStatus::Archived);
+}
+
+#[tokio::test]
+async fn archive_requires_an_archive_category() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("archive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.last_response(),
+        "Archiving is not enabled in this server!"
+    );
+    assert_eq!(
+        test.guild.channel_data(alpha).unwrap().parent_id,
+        Some(test.category_id)
+    );
+}
+
+#[tokio::test]
+async fn delete_archives_when_configured_unless_permanent() {
+    let mut test = TestGuild::new();
+    let archive_id = test.enable_archive(true);
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.guild.channel_data(alpha).unwrap().parent_id,
+        Some(archive_id)
+    );
+
+    let request = test
+        .command("delete", CREATOR, beta)
+        .flag("permanent", true)
+        .build();
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, beta);
+    test.run(&request).await.unwrap();
+    assert!(test.guild.channel_data(beta).is_none());
+}
+
+/// Create a channel as `user_id` and archive it
+async fn archived_channel(test: &TestGuild, user_id: u64, name: &str) -> ChannelId {
+    let channel_id = create_channel(test, user_id, name).await;
+    let request = test.request("archive", user_id, channel_id, &[]);
+    test.run(&request).await.unwrap();
+    channel_id
+}
+
+#[tokio::test]
+async fn unarchive_restores_channel_and_list_entry() {
+    let mut test = TestGuild::new();
+    test.enable_archive(false);
+    let alpha = archived_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("unarchive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+
+    let channel = test.guild.channel_data(alpha).unwrap();
+    assert_eq!(channel.name, "alpha");
+    assert_eq!(channel.parent_id, Some(test.category_id));
+    let owner = channel
+        .permission_overwrites
+        .iter()
+        .find(|o| o.kind == PermissionOverwriteType::Member(UserId::new(CREATOR)))
+        .unwrap();
+    assert!(owner.allow.contains(Permissions::SEND_MESSAGES));
+    assert!(owner.deny.is_empty());
+
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", alpha, CREATOR)]
+    );
+    assert!(test
+        .log_entries()
+        .contains(&format!("<#{}> unarchived by <@{}>", alpha, CREATOR)));
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.status, ChannelStatus::Active);
+    assert_eq!(record.name(), Some("alpha"));
+}
+
+#[tokio::test]
+async fn unarchive_only_works_in_archived_channels_of_the_owner() {
+    let mut test = TestGuild::new();
+    test.enable_archive(false);
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let beta = archived_channel(&test, CREATOR, "beta").await;
+
+    let request = test.request("unarchive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "This channel is not archived and cannot be used with /unarchive!"
+    );
+
+    let request = test.request("unarchive", OTHER_MEMBER, beta, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "This channel belongs to <@{}>, only they or a manager can use /unarchive here!",
+            CREATOR
+        )
+    );
+
+    let request = test.request("unarchive", MANAGER, beta, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.guild.channel_data(beta).unwrap().parent_id,
+        Some(test.category_id)
+    );
+}
+
+#[tokio::test]
+async fn unarchive_respects_the_owner_quota_and_taken_names() {
+    let mut test = TestGuild::with_config("max_channels_per_user = 1");
+    test.enable_archive(false);
+    let alpha = archived_channel(&test, CREATOR, "alpha").await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    // Even a manager cannot push the owner over their limit
+    let request = test.request("unarchive", MANAGER, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "<@{}> already owns 1 channel(s), the limit is 1. Delete or archive one before unarchiving this channel.",
+            CREATOR
+        )
+    );
+
+    // The name was reused while the channel was archived
+    create_channel(&test, OTHER_MEMBER, "alpha").await;
+    let request = test.request("delete", CREATOR, beta, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, beta);
+    test.run(&request).await.unwrap();
+
+    let request = test.request("unarchive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert!(test
+        .last_response()
+        .starts_with("A channel with this name already exists"));
+    assert_eq!(
+        test.guild.channel_data(alpha).unwrap().name,
+        "archived-alpha"
+    );
+}
+
+#[tokio::test]
+async fn delete_moves_channel_to_trash_when_configured() {
+    let mut test = TestGuild::new();
+    let trash_id = test.enable_trash();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert!(test
+        .last_response()
+        .contains("A manager can restore it with /restore for 7 day(s)."));
+    let request = test.click(&test.button("Delete"), CREATOR, alpha);
+    test.run(&request).await.unwrap();
+
+    // Hidden like the trash category, and out of the list
+    let channel = test.guild.channel_data(alpha).unwrap();
+    assert_eq!(channel.parent_id, Some(trash_id));
+    assert_eq!(
+        channel.permission_overwrites,
+        test.guild
+            .channel_data(trash_id)
+            .unwrap()
+            .permission_overwrites
+    );
+    assert!(test.list_entries().is_empty());
+
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.status, ChannelStatus::Trashed);
+    let trash = record.trash.unwrap();
+    assert_eq!(trash.trashed_by, CREATOR);
+    assert_eq!((trash.purge_at - trash.trashed_at).num_days(), 7);
+}
+
+#[tokio::test]
+async fn restore_brings_channel_back_from_trash() {
+    let mut test = TestGuild::new();
+    test.enable_trash();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let overwrites = test
+        .guild
+        .channel_data(alpha)
+        .unwrap()
+        .permission_overwrites;
+
+    let restore = test
+        .command("restore", MANAGER, test.list_channel_id)
+        .channel("channel", alpha)
+        .build();
+    test.run(&restore).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<#{}> is not in the trash!", alpha)
+    );
+
+    let request = test.request("delete", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let request = test.click(&test.button("Delete"), CREATOR, alpha);
+    test.run(&request).await.unwrap();
+
+    test.run(&restore).await.unwrap();
+
+    let channel = test.guild.channel_data(alpha).unwrap();
+    assert_eq!(channel.parent_id, Some(test.category_id));
+    assert_eq!(channel.permission_overwrites, overwrites);
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", alpha, CREATOR)]
+    );
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> restored from the trash by <@{}>",
+        alpha, MANAGER
+    )));
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.status, ChannelStatus::Active);
+    assert!(record.trash.is_none());
+}
+
+#[tokio::test]
+async fn transcript_is_sent_to_the_owner() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    test.guild.add_messages(alpha, CREATOR, 2);
+
+    let request = test.request("transcript", OTHER_MEMBER, alpha, &[]);
+    test.run(&request).await.unwrap();
+    assert!(test.guild.response_files().is_empty());
+
+    let request = test.request("transcript", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+
+    assert_eq!(
+        test.last_response(),
+        "Transcript of #alpha: 2 message(s), 0 thread(s)"
+    );
+    assert_eq!(test.guild.response_files().len(), 2);
+    assert!(test.guild.files(test.log_channel_id).is_empty());
+}
+
+#[tokio::test]
+async fn transfer_moves_ownership_to_new_member() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test
+        .command("transfer", CREATOR, alpha)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+
+    let members: Vec<PermissionOverwriteType> = test
+        .guild
+        .channel_data(alpha)
+        .unwrap()
+        .permission_overwrites
+        .into_iter()
+        .filter(|o| matches!(o.kind, PermissionOverwriteType::Member(_)))
+        .map(|o| o.kind)
+        .collect();
+    assert_eq!(
+        members,
+        vec![PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER))]
+    );
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", alpha, OTHER_MEMBER)]
+    );
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> transferred from <@{}> to <@{}> by <@{}>",
+        alpha, CREATOR, OTHER_MEMBER, CREATOR
+    )));
+    let record = test.registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.creator_id, CREATOR);
+    assert_eq!(record.owner_id(), OTHER_MEMBER);
+
+    // The new owner manages the channel, the creator no longer does
+    let request = test.request("rename", CREATOR, alpha, &[("name", "hijack")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "This channel belongs to <@{}>, only they or a manager can use /rename here!",
+            OTHER_MEMBER
+        )
+    );
+    let request = test.request("rename", OTHER_MEMBER, alpha, &[("name", "beta")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(alpha).unwrap().name, "beta");
+}
+
+#[tokio::test]
+async fn transfer_needs_the_special_role_unless_forced_by_a_manager() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let outsider = 600;
+    test.guild.add_member(outsider, Vec::new());
+
+    let request = test
+        .command("transfer", CREATOR, alpha)
+        .user("member", outsider)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "<@{}> does not have the role needed to own channels!",
+            outsider
+        )
+    );
+
+    let request = test
+        .command("transfer", CREATOR, alpha)
+        .user("member", outsider)
+        .flag("force", true)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(test.last_response(), "Only managers can force a transfer!");
+
+    let request = test
+        .command("transfer", CREATOR, alpha)
+        .user("member", 999)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "<@999> is not a member of this server!"
+    );
+
+    let request = test
+        .command("transfer", MANAGER, alpha)
+        .user("member", outsider)
+        .flag("force", true)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().owner_id(),
+        outsider
+    );
+}
+
+#[tokio::test]
+async fn co_owners_share_the_channel_but_not_its_ownership() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test
+        .command("owners", CREATOR, alpha)
+        .subcommand("add")
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<@{}> is now a co-owner of this channel!", OTHER_MEMBER)
+    );
+    let co_owner = test
+        .guild
+        .channel_data(alpha)
+        .unwrap()
+        .permission_overwrites
+        .into_iter()
+        .find(|o| o.kind == PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER)))
+        .unwrap();
+    // Co-owners manage messages, only the owner manages the channel itself
+    assert!(!co_owner.allow.contains(Permissions::MANAGE_CHANNELS));
+    assert!(co_owner.allow.contains(Permissions::MANAGE_MESSAGES));
+    assert_eq!(
+        test.list_entries(),
+        vec![format!(
+            "<#{}> by <@{}> with <@{}>",
+            alpha, CREATOR, OTHER_MEMBER
+        )]
+    );
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> co-owner <@{}> added by <@{}>",
+        alpha, OTHER_MEMBER, CREATOR
+    )));
+
+    // A co-owner manages the channel but cannot hand it over or change its owners
+    let request = test.request("rename", OTHER_MEMBER, alpha, &[("name", "beta")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(alpha).unwrap().name, "beta");
+    let request = test
+        .command("owners", OTHER_MEMBER, alpha)
+        .subcommand("remove")
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "This channel belongs to <@{}>, only they or a manager can use /owners here!",
+            CREATOR
+        )
+    );
+
+    let request = test
+        .command("owners", CREATOR, alpha)
+        .subcommand("remove")
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert!(!test
+        .guild
+        .channel_data(alpha)
+        .unwrap()
+        .permission_overwrites
+        .iter()
+        .any(|o| o.kind == PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER))));
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", alpha, CREATOR)]
+    );
+    let request = test.request("rename", OTHER_MEMBER, alpha, &[("name", "gamma")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(alpha).unwrap().name, "beta");
+}
+
+#[tokio::test]
+async fn owners_survive_a_lost_registry() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let request = test
+        .command("owners", CREATOR, alpha)
+        .subcommand("add")
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+
+    // Adopted again from the overwrites, which tell the owner apart
+    let registry = ChannelRegistry::load(temp_path("json")).unwrap();
+    get_managed_channels(&test.guild, &test.config, &registry)
+        .await
+        .unwrap();
+
+    let record = registry.get(alpha.get()).await.unwrap();
+    assert_eq!(record.owner_id(), CREATOR);
+    assert_eq!(record.co_owners, vec![OTHER_MEMBER]);
+}
+
+/// The overwrite of `kind` in `channel_id`, if any
+fn overwrite_of(
+    test: &TestGuild,
+    channel_id: ChannelId,
+    kind: PermissionOverwriteType,
+) -> Option<PermissionOverwrite> {
+    test.guild
+        .channel_data(channel_id)
+        .unwrap()
+        .permission_overwrites
+        .into_iter()
+        .find(|o| o.kind == kind)
+}
+
+#[tokio::test]
+async fn contributors_can_post_until_removed() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let member = PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER));
+    let role = PermissionOverwriteType::Role(RoleId::new(SPECIAL_ROLE_ID));
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("add")
+        .user("contributor", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<@{}> can now post in this channel!", OTHER_MEMBER)
+    );
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("add")
+        .role("contributor", SPECIAL_ROLE_ID)
+        .build();
+    test.run(&request).await.unwrap();
+
+    for kind in [member, role] {
+        let overwrite = overwrite_of(&test, alpha, kind).unwrap();
+        assert!(overwrite.allow.contains(Permissions::SEND_MESSAGES));
+        assert!(!overwrite.allow.contains(Permissions::MANAGE_CHANNELS));
+    }
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> contributor <@&{}> added by <@{}>",
+        alpha, SPECIAL_ROLE_ID, CREATOR
+    )));
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("list")
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!(
+            "Contributors of this channel: <@{}>, <@&{}>",
+            OTHER_MEMBER, SPECIAL_ROLE_ID
+        )
+    );
+
+    // Contributors cannot manage the channel
+    let request = test.request("rename", OTHER_MEMBER, alpha, &[("name", "beta")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(alpha).unwrap().name, "alpha");
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("remove")
+        .user("contributor", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert!(overwrite_of(&test, alpha, member).is_none());
+    assert!(overwrite_of(&test, alpha, role).is_some());
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> contributor <@{}> removed by <@{}>",
+        alpha, OTHER_MEMBER, CREATOR
+    )));
+}
+
+#[tokio::test]
+async fn contributors_are_muted_by_archive_and_restored_by_unarchive() {
+    let mut test = TestGuild::new();
+    test.enable_archive(false);
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+    let member = PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER));
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("add")
+        .user("contributor", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+
+    let request = test.request("archive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let overwrite = overwrite_of(&test, alpha, member).unwrap();
+    assert!(overwrite.deny.contains(Permissions::SEND_MESSAGES));
+
+    let request = test.request("unarchive", CREATOR, alpha, &[]);
+    test.run(&request).await.unwrap();
+    let overwrite = overwrite_of(&test, alpha, member).unwrap();
+    assert!(overwrite.allow.contains(Permissions::SEND_MESSAGES));
+    assert!(!overwrite.deny.contains(Permissions::SEND_MESSAGES));
+}
+
+#[tokio::test]
+async fn owners_cannot_be_contributors() {
+    let test = TestGuild::new();
+    let alpha = create_channel(&test, CREATOR, "alpha").await;
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("add")
+        .user("contributor", CREATOR)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<@{}> already owns this channel!", CREATOR)
+    );
+
+    let request = test
+        .command("contributors", CREATOR, alpha)
+        .subcommand("add")
+        .role("contributor", GUILD_ID)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(test.last_response(), "Member or role is not valid!");
+}
+
+#[tokio::test]
+async fn create_applies_a_permission_template() {
+    let test = TestGuild::with_config(&format!(
+        "[guilds.{}.templates.staff]\neveryone = {{ deny = [\"view_channel\"] }}\nroles = [{{ role_id = {}, allow = [\"view_channel\", \"send_messages\"] }}]",
+        GUILD_ID, SPECIAL_ROLE_ID
+    ));
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "alpha"), ("template", "open")],
+    );
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "Unknown template `open`! Available templates: staff"
+    );
+    assert!(test.guild.channel_named("alpha").is_none());
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "alpha"), ("template", "staff")],
+    );
+    test.run(&request).await.unwrap();
+    let alpha = test.guild.channel_named("alpha").unwrap().id;
+
+    let everyone = overwrite_of(
+        &test,
+        alpha,
+        PermissionOverwriteType::Role(RoleId::new(GUILD_ID)),
+    )
+    .unwrap();
+    assert_eq!(everyone.allow, Permissions::empty());
+    assert_eq!(everyone.deny, Permissions::VIEW_CHANNEL);
+    let role = overwrite_of(
+        &test,
+        alpha,
+        PermissionOverwriteType::Role(RoleId::new(SPECIAL_ROLE_ID)),
+    )
+    .unwrap();
+    assert_eq!(
+        role.allow,
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES
+    );
+    // The owner keeps the default overwrite, the template does not set one
+    let owner = overwrite_of(
+        &test,
+        alpha,
+        PermissionOverwriteType::Member(UserId::new(CREATOR)),
+    )
+    .unwrap();
+    assert!(owner.allow.contains(Permissions::MANAGE_CHANNELS));
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> created from template `staff` by <@{}>",
+        alpha, CREATOR
+    )));
+}
+
+#[tokio::test]
+async fn template_owner_rule_follows_new_owners() {
+    let test = TestGuild::with_config(&format!(
+        "[guilds.{}.templates.quiet]\nowner = {{ allow = [\"view_channel\", \"manage_channels\"] }}",
+        GUILD_ID
+    ));
+    let alpha = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "alpha")
+            .string("template", "quiet"),
+    )
+    .await;
+    let allowed = |user_id: u64| {
+        overwrite_of(
+            &test,
+            alpha,
+            PermissionOverwriteType::Member(UserId::new(user_id)),
+        )
+        .unwrap()
+        .allow
+    };
+    let template_owner =
+        |user_id: u64| allowed(user_id) == Permissions::VIEW_CHANNEL | Permissions::MANAGE_CHANNELS;
+    assert!(template_owner(CREATOR));
+
+    let request = test
+        .command("transfer", CREATOR, alpha)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert!(template_owner(OTHER_MEMBER));
+
+    let request = test
+        .command("owners", OTHER_MEMBER, alpha)
+        .subcommand("add")
+        .user("member", CREATOR)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        allowed(CREATOR),
+        Permissions::VIEW_CHANNEL | Permissions::MANAGE_MESSAGES
+    );
+    assert!(template_owner(OTHER_MEMBER));
+}
+
+#[tokio::test]
+async fn forums_are_managed_from_their_posts() {
+    let test = TestGuild::new();
+    let forum = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "ideas")
+            .string("type", "forum"),
+    )
+    .await;
+    assert_eq!(
+        test.guild.channel_data(forum).unwrap().kind,
+        ChannelType::Forum
+    );
+    let everyone = overwrite_of(
+        &test,
+        forum,
+        PermissionOverwriteType::Role(RoleId::new(GUILD_ID)),
+    )
+    .unwrap();
+    assert!(everyone.allow.contains(Permissions::SEND_MESSAGES));
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", forum, CREATOR)]
+    );
+
+    // Commands used in a post act on the forum
+    let post = test
+        .guild
+        .add_channel("first-idea", ChannelType::PublicThread, Some(forum));
+    let request = test.request("rename", CREATOR, post, &[("name", "proposals")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(forum).unwrap().name, "proposals");
+    assert_eq!(test.guild.channel_data(post).unwrap().name, "first-idea");
+
+    let request = test.request("description", CREATOR, post, &[("text", "Share ideas")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.guild.channel_data(forum).unwrap().topic.as_deref(),
+        Some("Share ideas")
+    );
+
+    let request = test.request("delete", CREATOR, post, &[]);
+    test.run(&request).await.unwrap();
+    assert!(test.last_response().contains("It has 1 post(s)"));
+    let request = test.click(&test.button("Delete"), CREATOR, post);
+    test.run(&request).await.unwrap();
+    assert!(test.guild.channel_data(forum).is_none());
+    assert!(test.list_entries().is_empty());
+}
+
+/// Permissions `user_id` ends up with in `channel_id`, resolved like Discord does
+async fn permissions_in(test: &TestGuild, channel_id: ChannelId, user_id: u64) -> Permissions {
+    let channel = test.guild.channel_data(channel_id).unwrap();
+    let roles = test.guild.guild_roles(test.guild.guild_id()).await.unwrap();
+    let member = test
+        .guild
+        .member(test.guild.guild_id(), UserId::new(user_id))
+        .await
+        .unwrap();
+    channel_permissions(&channel, &roles, UserId::new(user_id), &member.roles)
+}
+
+#[tokio::test]
+async fn archived_forums_and_voice_channels_are_closed_to_everyone() {
+    let mut test = TestGuild::new();
+    test.enable_archive(false);
+    let everyone = PermissionOverwriteType::Role(RoleId::new(GUILD_ID));
+    for (name, kind, open) in [
+        (
+            "ideas",
+            "forum",
+            Permissions::SEND_MESSAGES | Permissions::SEND_MESSAGES_IN_THREADS,
+        ),
+        ("lounge", "voice", Permissions::CONNECT | Permissions::SPEAK),
+    ] {
+        let channel_id = create_with(
+            &test,
+            test.command("create", CREATOR, test.list_channel_id)
+                .string("name", name)
+                .string("type", kind),
+        )
+        .await;
+        let original = overwrite_of(&test, channel_id, everyone).unwrap();
+        assert!(permissions_in(&test, channel_id, OTHER_MEMBER)
+            .await
+            .contains(open));
+
+        let request = test.request("archive", CREATOR, channel_id, &[]);
+        test.run(&request).await.unwrap();
+        let frozen = overwrite_of(&test, channel_id, everyone).unwrap();
+        assert!(frozen.deny.contains(open), "{}", name);
+        for user_id in [CREATOR, OTHER_MEMBER] {
+            let permissions = permissions_in(&test, channel_id, user_id).await;
+            assert!(!permissions.intersects(open), "{} {}", name, user_id);
+        }
+
+        let request = test.request("unarchive", CREATOR, channel_id, &[]);
+        test.run(&request).await.unwrap();
+        let restored = overwrite_of(&test, channel_id, everyone).unwrap();
+        assert_eq!(
+            (restored.allow, restored.deny),
+            (original.allow, original.deny)
+        );
+    }
+}
+
+#[tokio::test]
+async fn announcement_and_voice_channels_are_listed() {
+    let test = TestGuild::new();
+    let news = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "news")
+            .string("type", "news"),
+    )
+    .await;
+    let voice = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "Team Room")
+            .string("type", "voice"),
+    )
+    .await;
+    assert_eq!(
+        test.guild.channel_data(news).unwrap().kind,
+        ChannelType::News
+    );
+    assert_eq!(
+        test.guild.channel_data(voice).unwrap().kind,
+        ChannelType::Voice
+    );
+    let everyone = overwrite_of(
+        &test,
+        voice,
+        PermissionOverwriteType::Role(RoleId::new(GUILD_ID)),
+    )
+    .unwrap();
+    assert!(everyone.allow.contains(Permissions::CONNECT));
+
+    // Voice channels have no topic, their description only lives in the registry
+    let request = test.request("description", CREATOR, voice, &[("text", "Weekly calls")]);
+    test.run(&request).await.unwrap();
+    assert_eq!(test.guild.channel_data(voice).unwrap().topic, None);
+    let request = test.request("updatelist", MANAGER, test.list_channel_id, &[]);
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.list_entries(),
+        vec![
+            format!("<#{}> - Weekly calls by <@{}>", voice, CREATOR),
+            format!("<#{}> by <@{}>", news, CREATOR),
+        ]
+    );
+
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "stage"), ("type", "stage")],
+    );
+    test.run(&request).await.unwrap();
+    assert_eq!(test.last_response(), "Unknown channel type `stage`!");
+}
+
+#[tokio::test]
+async fn bot_keeps_access_to_hidden_channels() {
+    let test = TestGuild::with_config(&format!(
+        "[guilds.{}.templates.staff]\neveryone = {{ deny = [\"view_channel\"] }}",
+        GUILD_ID
+    ));
+    let needed = Permissions::VIEW_CHANNEL
+        | Permissions::SEND_MESSAGES
+        | Permissions::READ_MESSAGE_HISTORY
+        | Permissions::MANAGE_CHANNELS
+        | Permissions::MANAGE_ROLES;
+
+    let alpha = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "alpha")
+            .flag("private", true),
+    )
+    .await;
+    let beta = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "beta")
+            .string("template", "staff"),
+    )
+    .await;
+
+    for channel_id in [alpha, beta] {
+        assert!(!permissions_in(&test, channel_id, OTHER_MEMBER)
+            .await
+            .contains(Permissions::VIEW_CHANNEL));
+        assert!(permissions_in(&test, channel_id, BOT_USER_ID)
+            .await
+            .contains(needed));
+    }
+
+    // The bot is not taken for an owner when the registry is lost
+    let registry = ChannelRegistry::load(temp_path("json")).unwrap();
+    get_managed_channels(&test.guild, &test.config, &registry)
+        .await
+        .unwrap();
+    assert_eq!(registry.get(alpha.get()).await.unwrap().owner_id(), CREATOR);
+}
+
+#[tokio::test]
+async fn private_channels_let_in_invited_members() {
+    let test = TestGuild::new();
+    let alpha = create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "alpha")
+            .flag("private", true),
+    )
+    .await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+    let member = PermissionOverwriteType::Member(UserId::new(OTHER_MEMBER));
+
+    let everyone = overwrite_of(
+        &test,
+        alpha,
+        PermissionOverwriteType::Role(RoleId::new(GUILD_ID)),
+    )
+    .unwrap();
+    assert!(everyone.deny.contains(Permissions::VIEW_CHANNEL));
+    assert!(!everyone.allow.contains(Permissions::VIEW_CHANNEL));
+    assert_eq!(
+        test.list_entries(),
+        vec![
+            format!("🔒 <#{}> by <@{}>", alpha, CREATOR),
+            format!("<#{}> by <@{}>", beta, CREATOR),
+        ]
+    );
+
+    let request = test
+        .command("invite", CREATOR, alpha)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<@{}> can now see this channel!", OTHER_MEMBER)
+    );
+    assert!(overwrite_of(&test, alpha, member)
+        .unwrap()
+        .allow
+        .contains(Permissions::VIEW_CHANNEL));
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> <@{}> invited by <@{}>",
+        alpha, OTHER_MEMBER, CREATOR
+    )));
+
+    let request = test
+        .command("kick", CREATOR, alpha)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert!(overwrite_of(&test, alpha, member).is_none());
+    assert!(test.log_entries().contains(&format!(
+        "<#{}> <@{}> kicked by <@{}>",
+        alpha, OTHER_MEMBER, CREATOR
+    )));
+    let request = test
+        .command("kick", CREATOR, alpha)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        format!("<@{}> was not invited to this channel!", OTHER_MEMBER)
+    );
+
+    let request = test
+        .command("invite", CREATOR, beta)
+        .user("member", OTHER_MEMBER)
+        .build();
+    test.run(&request).await.unwrap();
+    assert_eq!(
+        test.last_response(),
+        "This channel is not private, everyone can already see it!"
+    );
+}
+
+#[tokio::test]
+async fn private_channels_can_be_left_out_of_the_list() {
+    let test = TestGuild::with_config(r#"private_listing = "hidden""#);
+    create_with(
+        &test,
+        test.command("create", CREATOR, test.list_channel_id)
+            .string("name", "alpha")
+            .flag("private", true),
+    )
+    .await;
+    let beta = create_channel(&test, CREATOR, "beta").await;
+
+    assert_eq!(
+        test.list_entries(),
+        vec![format!("<#{}> by <@{}>", beta, CREATOR)]
+    );
+}
\ No newline at end of file
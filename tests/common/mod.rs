@@ -0,0 +1,347 @@
+#![allow(dead_code)]
+
+use discord_channel_bot::backend::fake::BOT_USER_ID;
+use discord_channel_bot::backend::{
+    CommandRequest, FakeGuild, InteractionHandle, MemberData, OptionValue,
+};
+use discord_channel_bot::commands::{CommandContext, CommandSet};
+use discord_channel_bot::config::{Config, GuildConfig, SharedConfig};
+use discord_channel_bot::registry::ChannelRegistry;
+use serenity::all::{
+    ChannelId, ChannelType, InteractionId, PermissionOverwrite, PermissionOverwriteType,
+    Permissions, RoleId, UserId,
+};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const GUILD_ID: u64 = 1_000;
+pub const SPECIAL_ROLE_ID: u64 = 10;
+pub const MANAGER_ROLE_ID: u64 = 20;
+pub const BOT_ROLE_ID: u64 = 30;
+
+pub const CREATOR: u64 = 500;
+pub const OTHER_MEMBER: u64 = 501;
+pub const MANAGER: u64 = 502;
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Unique temporary file for a test, removed before use
+pub fn temp_path(extension: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "channel-bot-test-{}-{}.{}",
+        std::process::id(),
+        NEXT_FILE.fetch_add(1, Ordering::SeqCst),
+        extension
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Category, log and list channels the bot expects in a guild
+pub struct BotChannels {
+    pub category_id: ChannelId,
+    pub log_channel_id: ChannelId,
+    pub list_channel_id: ChannelId,
+}
+
+impl BotChannels {
+    /// Add the channels, the configured roles and the test members with their roles,
+    /// the bot holding the permissions its commands need
+    pub fn add_to(guild: &FakeGuild) -> Self {
+        guild.add_role(SPECIAL_ROLE_ID, "special", Permissions::empty());
+        guild.add_role(MANAGER_ROLE_ID, "manager", Permissions::empty());
+        let bot_role = guild.add_role(
+            BOT_ROLE_ID,
+            "bot",
+            Permissions::MANAGE_CHANNELS
+                | Permissions::MANAGE_ROLES
+                | Permissions::MANAGE_MESSAGES
+                | Permissions::CREATE_PUBLIC_THREADS
+                | Permissions::ATTACH_FILES,
+        );
+        guild.add_member(BOT_USER_ID, vec![bot_role]);
+        for user_id in [CREATOR, OTHER_MEMBER, MANAGER] {
+            guild.add_member(user_id, roles_of(user_id));
+        }
+
+        let category_id = guild.add_channel("Channels", ChannelType::Category, None);
+        let log_channel_id = guild.add_channel("log", ChannelType::Text, Some(category_id));
+        let list_channel_id = guild.add_channel("list", ChannelType::Text, Some(category_id));
+        Self {
+            category_id,
+            log_channel_id,
+            list_channel_id,
+        }
+    }
+
+    /// `config.toml` table for a guild using these channels, followed by `extra`
+    pub fn guild_table(&self, guild_id: u64, extra: &str) -> String {
+        format!(
+            r#"
+[guilds.{}]
+category_id = {}
+log_channel_id = {}
+list_channel_id = {}
+special_role_id = {}
+manager_role_id = {}
+{}
+"#,
+            guild_id,
+            self.category_id,
+            self.log_channel_id,
+            self.list_channel_id,
+            SPECIAL_ROLE_ID,
+            MANAGER_ROLE_ID,
+            extra
+        )
+    }
+}
+
+/// A fake guild with the category, log and list channels the bot expects
+pub struct TestGuild {
+    pub guild: FakeGuild,
+    pub config: GuildConfig,
+    pub registry: ChannelRegistry,
+    pub shared: SharedConfig,
+    pub category_id: ChannelId,
+    pub log_channel_id: ChannelId,
+    pub list_channel_id: ChannelId,
+    registry_path: PathBuf,
+}
+
+impl TestGuild {
+    pub fn new() -> Self {
+        Self::with_config("")
+    }
+
+    /// Build the guild, appending `extra` to its table in the generated `config.toml`
+    pub fn with_config(extra: &str) -> Self {
+        let guild = FakeGuild::new(GUILD_ID);
+        let channels = BotChannels::add_to(&guild);
+        let registry_path = temp_path("json");
+
+        let full_config = Config::parse(&format!(
+            "token = \"test\"\nregistry_path = \"{}\"\n{}",
+            registry_path.display(),
+            channels.guild_table(GUILD_ID, extra)
+        ))
+        .unwrap();
+        let config = full_config.guild(guild.guild_id()).unwrap().clone();
+        let registry = ChannelRegistry::load(&registry_path).unwrap();
+        let shared = SharedConfig::new(temp_path("toml").to_str().unwrap(), full_config);
+
+        Self {
+            guild,
+            config,
+            registry,
+            shared,
+            category_id: channels.category_id,
+            log_channel_id: channels.log_channel_id,
+            list_channel_id: channels.list_channel_id,
+            registry_path,
+        }
+    }
+
+    pub fn request(
+        &self,
+        name: &str,
+        user_id: u64,
+        channel_id: ChannelId,
+        options: &[(&str, &str)],
+    ) -> CommandRequest {
+        request_in(&self.guild, name, user_id, channel_id, options)
+    }
+
+    /// Start a slash command invocation taking a subcommand or typed options
+    pub fn command(&self, name: &str, user_id: u64, channel_id: ChannelId) -> RequestBuilder {
+        RequestBuilder(self.request(name, user_id, channel_id, &[]))
+    }
+
+    /// Click on a button with `custom_id`, as `user_id` in `channel_id`
+    pub fn click(&self, custom_id: &str, user_id: u64, channel_id: ChannelId) -> CommandRequest {
+        let (name, action) = custom_id.split_once(':').unwrap();
+        let mut request = self.request(name, user_id, channel_id, &[]);
+        request.component = Some(action.to_string());
+        request
+    }
+
+    /// Custom id of the button labelled `label` in the latest reply with buttons
+    pub fn button(&self, label: &str) -> String {
+        self.guild
+            .buttons()
+            .into_iter()
+            .find(|b| b.label == label)
+            .map(|b| b.custom_id)
+            .unwrap_or_else(|| panic!("no `{}` button", label))
+    }
+
+    /// Add an archive category and enable archiving, making /delete archive when
+    /// `archive_on_delete` is set
+    pub fn enable_archive(&mut self, archive_on_delete: bool) -> ChannelId {
+        let archive_id = self
+            .guild
+            .add_channel("Archive", ChannelType::Category, None);
+        self.config.archive_category_id = Some(archive_id.get());
+        self.config.archive_on_delete = archive_on_delete;
+        archive_id
+    }
+
+    /// Add a trash category hidden from @everyone and send deleted channels there
+    pub fn enable_trash(&mut self) -> ChannelId {
+        let trash_id = self.guild.add_channel("Trash", ChannelType::Category, None);
+        self.guild.set_permission_overwrites(
+            trash_id,
+            vec![PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: Permissions::VIEW_CHANNEL,
+                kind: PermissionOverwriteType::Role(RoleId::new(GUILD_ID)),
+            }],
+        );
+        self.config.trash_category_id = Some(trash_id.get());
+        trash_id
+    }
+
+    /// The registry as the bot would load it after a restart
+    pub fn reload_registry(&self) -> ChannelRegistry {
+        ChannelRegistry::load(&self.registry_path).unwrap()
+    }
+
+    /// Run a request through the command set, as the gateway handler does
+    pub async fn run(&self, request: &CommandRequest) -> anyhow::Result<()> {
+        dispatch(
+            &self.guild,
+            &self.config,
+            &self.registry,
+            &self.shared,
+            request,
+        )
+        .await
+    }
+
+    /// Descriptions of every embed in the list channel, in display order
+    pub fn list_entries(&self) -> Vec<String> {
+        self.guild
+            .channel_messages(self.list_channel_id)
+            .into_iter()
+            .flat_map(|m| m.embeds)
+            .map(|e| e.description)
+            .collect()
+    }
+
+    /// Descriptions of every embed in the log channel, oldest first
+    pub fn log_entries(&self) -> Vec<String> {
+        self.guild
+            .channel_messages(self.log_channel_id)
+            .into_iter()
+            .flat_map(|m| m.embeds)
+            .map(|e| e.description)
+            .collect()
+    }
+
+    pub fn last_response(&self) -> String {
+        self.guild.responses().last().cloned().unwrap_or_default()
+    }
+}
+
+/// Roles of a test member
+pub fn roles_of(user_id: u64) -> Vec<RoleId> {
+    match user_id {
+        CREATOR | OTHER_MEMBER => vec![RoleId::new(SPECIAL_ROLE_ID)],
+        MANAGER => vec![RoleId::new(SPECIAL_ROLE_ID), RoleId::new(MANAGER_ROLE_ID)],
+        _ => Vec::new(),
+    }
+}
+
+/// Slash command invocation in `guild` with string options
+pub fn request_in(
+    guild: &FakeGuild,
+    name: &str,
+    user_id: u64,
+    channel_id: ChannelId,
+    options: &[(&str, &str)],
+) -> CommandRequest {
+    CommandRequest {
+        name: name.to_string(),
+        interaction: InteractionHandle {
+            id: InteractionId::new(1),
+            token: "token".to_string(),
+        },
+        guild_id: Some(guild.guild_id()),
+        channel_id,
+        user_id: UserId::new(user_id),
+        member: Some(MemberData {
+            roles: roles_of(user_id),
+        }),
+        subcommand: None,
+        options: options
+            .iter()
+            .map(|(n, v)| (n.to_string(), OptionValue::String(v.to_string())))
+            .collect(),
+        component: None,
+    }
+}
+
+/// Slash command invocation built option by option
+pub struct RequestBuilder(CommandRequest);
+
+impl RequestBuilder {
+    pub fn subcommand(mut self, name: &str) -> Self {
+        self.0.subcommand = Some(name.to_string());
+        self
+    }
+
+    pub fn option(mut self, name: &str, value: OptionValue) -> Self {
+        self.0.options.push((name.to_string(), value));
+        self
+    }
+
+    pub fn string(self, name: &str, value: &str) -> Self {
+        self.option(name, OptionValue::String(value.to_string()))
+    }
+
+    pub fn user(self, name: &str, user_id: u64) -> Self {
+        self.option(name, OptionValue::User(UserId::new(user_id)))
+    }
+
+    pub fn role(self, name: &str, role_id: u64) -> Self {
+        self.option(name, OptionValue::Role(RoleId::new(role_id)))
+    }
+
+    pub fn channel(self, name: &str, channel_id: ChannelId) -> Self {
+        self.option(name, OptionValue::Channel(channel_id))
+    }
+
+    pub fn flag(self, name: &str, value: bool) -> Self {
+        self.option(name, OptionValue::Boolean(value))
+    }
+
+    pub fn build(self) -> CommandRequest {
+        self.0
+    }
+}
+
+/// Dispatch `request` in `guild` through the bot's command set
+pub async fn dispatch(
+    guild: &FakeGuild,
+    config: &GuildConfig,
+    registry: &ChannelRegistry,
+    shared: &SharedConfig,
+    request: &CommandRequest,
+) -> anyhow::Result<()> {
+    CommandSet::new()
+        .dispatch(CommandContext {
+            backend: guild,
+            request,
+            config,
+            registry,
+            shared_config: shared,
+            bot_user_id: UserId::new(BOT_USER_ID),
+        })
+        .await
+}
+
+impl Drop for TestGuild {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.registry_path);
+    }
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use common::temp_path;
+use discord_channel_bot::config::{CommandScope, Config, ListStyle};
+use serenity::all::{GuildId, Permissions, RoleId};
+use std::collections::BTreeMap;
+
+const FILE: &str = r#"
+token = "file-token"
+
+[guilds.100]
+category_id = 1
+log_channel_id = 2
+list_channel_id = 3
+special_role_id = 4
+manager_role_id = 5
+"#;
+
+fn env(vars: &[(&str, &str)]) -> BTreeMap<String, String> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn env_overrides_the_only_guild() {
+    let config = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[
+            ("CHANNEL_BOT_CATEGORY_ID", "11"),
+            ("CHANNEL_BOT_LIST_STYLE", "compact"),
+        ]),
+    )
+    .unwrap();
+
+    let guild = config.guild(GuildId::new(100)).unwrap();
+    assert_eq!(guild.category_id, 11);
+    assert_eq!(guild.log_channel_id, 2);
+    assert_eq!(guild.list_style, ListStyle::Compact);
+}
+
+#[test]
+fn environment_alone_is_enough() {
+    let config = Config::from_sources(
+        "config.toml",
+        None,
+        &env(&[
+            ("CHANNEL_BOT_TOKEN", "env-token"),
+            ("CHANNEL_BOT_GUILD_ID", "200"),
+            ("CHANNEL_BOT_CATEGORY_ID", "1"),
+            ("CHANNEL_BOT_LOG_CHANNEL_ID", "2"),
+            ("CHANNEL_BOT_LIST_CHANNEL_ID", "3"),
+            ("CHANNEL_BOT_SPECIAL_ROLE_ID", "4"),
+            ("CHANNEL_BOT_MANAGER_ROLE_ID", "5"),
+        ]),
+    )
+    .unwrap();
+
+    assert_eq!(config.token, "env-token");
+    assert_eq!(config.guild(GuildId::new(200)).unwrap().manager_role_id, 5);
+    config.validate().unwrap();
+}
+
+#[test]
+fn guild_specific_variable_wins() {
+    let config = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[
+            ("CHANNEL_BOT_CATEGORY_ID", "11"),
+            ("CHANNEL_BOT_GUILD_100_CATEGORY_ID", "12"),
+        ]),
+    )
+    .unwrap();
+
+    assert_eq!(config.guild(GuildId::new(100)).unwrap().category_id, 12);
+}
+
+#[test]
+fn token_precedence() {
+    let token_file = temp_path("token");
+    std::fs::write(&token_file, "file-secret\n").unwrap();
+    let token_file_name = token_file.to_str().unwrap();
+
+    let from_file = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[("CHANNEL_BOT_TOKEN_FILE", token_file_name)]),
+    )
+    .unwrap();
+    assert_eq!(from_file.token, "file-secret");
+
+    let from_env = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[
+            ("CHANNEL_BOT_TOKEN_FILE", token_file_name),
+            ("CHANNEL_BOT_TOKEN", "env-secret"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(from_env.token, "env-secret");
+
+    let _ = std::fs::remove_file(token_file);
+}
+
+#[test]
+fn missing_setting_is_named() {
+    let error = Config::from_sources(
+        "config.toml",
+        Some(&FILE.replace("list_channel_id = 3\n", "")),
+        &BTreeMap::new(),
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(error.contains("`list_channel_id`"), "{}", error);
+    assert!(error.contains("CHANNEL_BOT_LIST_CHANNEL_ID"), "{}", error);
+}
+
+#[test]
+fn unqualified_variable_needs_a_guild_choice() {
+    let two_guilds = format!(
+        "{}{}",
+        FILE,
+        FILE.replace("token = \"file-token\"", "")
+            .replace("100", "101")
+    );
+
+    let error = Config::from_sources(
+        "config.toml",
+        Some(&two_guilds),
+        &env(&[("CHANNEL_BOT_CATEGORY_ID", "11")]),
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(error.contains("CHANNEL_BOT_GUILD_ID"), "{}", error);
+}
+
+#[test]
+fn command_scope_defaults_to_global() {
+    let config = Config::from_sources("config.toml", Some(FILE), &BTreeMap::new()).unwrap();
+    assert_eq!(config.command_scope, CommandScope::Global);
+
+    let config = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[("CHANNEL_BOT_COMMAND_SCOPE", "guild")]),
+    )
+    .unwrap();
+    assert_eq!(config.command_scope, CommandScope::Guild);
+}
+
+#[test]
+fn channel_limit_prefers_the_highest_role_limit() {
+    let content = format!(
+        "{}max_channels_per_user = 2\n[[guilds.100.role_channel_limits]]\nrole_id = 7\nmax_channels = 5\n[[guilds.100.role_channel_limits]]\nrole_id = 8\nmax_channels = 10\n",
+        FILE
+    );
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+    let guild = config.guild(GuildId::new(100)).unwrap();
+
+    assert_eq!(guild.channel_limit(&[]), Some(2));
+    assert_eq!(guild.channel_limit(&[RoleId::new(7)]), Some(5));
+    assert_eq!(
+        guild.channel_limit(&[RoleId::new(7), RoleId::new(8)]),
+        Some(10)
+    );
+
+    let config = Config::from_sources(
+        "config.toml",
+        Some(FILE),
+        &env(&[("CHANNEL_BOT_MAX_CHANNELS_PER_USER", "3")]),
+    )
+    .unwrap();
+    let guild = config.guild(GuildId::new(100)).unwrap();
+    assert_eq!(guild.channel_limit(&[RoleId::new(7)]), Some(3));
+}
+
+#[test]
+fn invalid_naming_pattern_is_rejected() {
+    let content = format!("{}[guilds.100.naming]\npattern = \"proj-(\"\n", FILE);
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("naming.pattern is not valid"), "{}", error);
+}
+
+#[test]
+fn empty_naming_prefix_is_rejected() {
+    let content = format!("{}[guilds.100.naming]\nrequired_prefix = \"--\"\n", FILE);
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("naming.required_prefix"), "{}", error);
+}
+
+#[test]
+fn archive_on_delete_needs_an_archive_category() {
+    let content = format!("{}archive_on_delete = true\n", FILE);
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("requires archive_category_id"), "{}", error);
+
+    let env = env(&[("CHANNEL_BOT_ARCHIVE_CATEGORY_ID", "6")]);
+    let config = Config::from_sources("config.toml", Some(&content), &env).unwrap();
+    config.validate().unwrap();
+    assert_eq!(config.guilds[&100].archive_category_id, Some(6));
+}
+
+#[test]
+fn template_permissions_must_be_known() {
+    let content = format!(
+        "{}[guilds.100.templates.open]\neveryone = {{ allow = [\"view_channel\", \"Send_Messages\"] }}\nroles = [{{ role_id = 7, deny = [\"view_channel\"] }}]\n",
+        FILE
+    );
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+    config.validate().unwrap();
+    let template = config.guilds[&100].template("open").unwrap();
+    assert_eq!(
+        template.everyone.as_ref().unwrap().allow(),
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES
+    );
+
+    let content = format!(
+        "{}[guilds.100.templates.staff]\nowner = {{ allow = [\"send_mesages\"] }}\n",
+        FILE
+    );
+    let config = Config::from_sources("config.toml", Some(&content), &BTreeMap::new()).unwrap();
+    let error = config.validate().unwrap_err().to_string();
+    assert!(
+        error.contains("template `staff`: owner.allow: `send_mesages` is not a Discord permission"),
+        "{}",
+        error
+    );
+}
\ No newline at end of file
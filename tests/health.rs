@@ -0,0 +1,135 @@
+mod common;
+
+use common::{TestGuild, BOT_ROLE_ID, GUILD_ID, MANAGER_ROLE_ID};
+use discord_channel_bot::backend::fake::BOT_USER_ID;
+use discord_channel_bot::health::check_guild;
+use serenity::all::{
+    ChannelType, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, UserId,
+};
+
+#[tokio::test]
+async fn valid_setup_enables_every_command() {
+    let t = TestGuild::new();
+
+    let health = check_guild(&t.guild, &t.config, UserId::new(BOT_USER_ID)).await;
+
+    assert!(health.is_healthy(), "{}", health.report());
+    assert!(health.disabled_commands().is_empty());
+    assert!(health.report().contains("All commands enabled"));
+}
+
+#[tokio::test]
+async fn wrong_channel_types_disable_channel_commands() {
+    let t = TestGuild::new();
+    // The category id points at a text channel and the list channel is gone
+    let text = t.guild.add_channel("general", ChannelType::Text, None);
+    let mut config = t.config.clone();
+    config.category_id = text.get();
+    config.list_channel_id = 9_999;
+
+    let health = check_guild(&t.guild, &config, UserId::new(BOT_USER_ID)).await;
+
+    let report = health.report();
+    assert!(report.contains(&format!("[FAIL] category {} is a category", text)));
+    assert!(report.contains("[FAIL] list channel 9999 is a text channel: not found"));
+    assert!(health.disabled_reason("create").is_some());
+    assert!(health.disabled_reason("updatelist").is_some());
+    assert_eq!(health.disabled_reason("reloadconfig"), None);
+}
+
+#[tokio::test]
+async fn missing_role_disables_only_its_commands() {
+    let t = TestGuild::new();
+    t.guild.remove_role(RoleId::new(MANAGER_ROLE_ID));
+
+    let health = check_guild(&t.guild, &t.config, UserId::new(BOT_USER_ID)).await;
+
+    assert_eq!(
+        health.disabled_commands().into_iter().collect::<Vec<_>>(),
+        vec!["reloadconfig", "restore", "updatelist"]
+    );
+    assert!(health
+        .disabled_reason("updatelist")
+        .unwrap()
+        .contains("manager role 20 exists: not found"));
+}
+
+#[tokio::test]
+async fn missing_permissions_are_named() {
+    let t = TestGuild::new();
+    // Without its role the bot only has the @everyone permissions
+    t.guild.add_member(BOT_USER_ID, Vec::new());
+
+    let health = check_guild(&t.guild, &t.config, UserId::new(BOT_USER_ID)).await;
+
+    let reason = health.disabled_reason("create").unwrap();
+    assert!(reason.contains("Manage Channels"), "{}", reason);
+    assert!(reason.contains("Manage Roles"), "{}", reason);
+    assert!(health
+        .report()
+        .contains("Disabled commands: /archive, /contributors, /create"));
+}
+
+#[tokio::test]
+async fn templates_need_the_permissions_they_set() {
+    let t = TestGuild::with_config(&format!(
+        "[guilds.{}.templates.hooks]\nowner = {{ allow = [\"manage_webhooks\"] }}",
+        GUILD_ID
+    ));
+
+    let health = check_guild(&t.guild, &t.config, UserId::new(BOT_USER_ID)).await;
+
+    let reason = health.disabled_reason("create").unwrap();
+    assert!(
+        reason.contains("bot can apply permission template `hooks`: missing Manage Webhooks"),
+        "{}",
+        reason
+    );
+    assert_eq!(health.disabled_reason("rename"), None);
+}
+
+#[tokio::test]
+async fn channel_overwrites_are_applied() {
+    let t = TestGuild::new();
+    // Deny the bot's role from posting in the log channel
+    t.guild.set_permission_overwrites(
+        t.log_channel_id,
+        vec![PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::SEND_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId::new(BOT_ROLE_ID)),
+        }],
+    );
+
+    let health = check_guild(&t.guild, &t.config, UserId::new(BOT_USER_ID)).await;
+
+    let reason = health.disabled_reason("rename").unwrap();
+    assert!(reason.contains("bot can post in the log channel: missing Send Messages"));
+    assert_eq!(health.disabled_reason("reloadconfig"), None);
+}
+
+#[tokio::test]
+async fn missing_archive_category_disables_archiving() {
+    let mut t = TestGuild::new();
+    t.enable_archive(true);
+    let mut config = t.config.clone();
+    config.archive_category_id = Some(9_999);
+
+    let health = check_guild(&t.guild, &config, UserId::new(BOT_USER_ID)).await;
+
+    assert_eq!(
+        health.disabled_commands().into_iter().collect::<Vec<_>>(),
+        vec!["archive", "delete"]
+    );
+    assert!(health
+        .report()
+        .contains("[FAIL] archive category 9999 is a category: not found"));
+
+    // Without archive_on_delete, /delete keeps working
+    config.archive_on_delete = false;
+    let health = check_guild(&t.guild, &config, UserId::new(BOT_USER_ID)).await;
+    assert_eq!(
+        health.disabled_commands().into_iter().collect::<Vec<_>>(),
+        vec!["archive"]
+    );
+}
\ No newline at end of file
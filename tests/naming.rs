@@ -0,0 +1,177 @@
+use discord_channel_bot::config::NamingPolicy;
+use discord_channel_bot::naming::{
+    check_policy, normalize_channel_name, validate_channel_name, NameError, MAX_NAME_LENGTH,
+};
+use serenity::all::ChannelType;
+
+fn names(list: &[(u64, &str)]) -> Vec<(u64, String)> {
+    list.iter().map(|(id, n)| (*id, n.to_string())).collect()
+}
+
+#[test]
+fn normalizes_like_discord() {
+    assert_eq!(
+        normalize_channel_name("Project Alpha", ChannelType::Text),
+        "project-alpha"
+    );
+    assert_eq!(
+        normalize_channel_name("  my   team!! ", ChannelType::Text),
+        "my-team"
+    );
+    assert_eq!(normalize_channel_name("--a--b--", ChannelType::Text), "a-b");
+    assert_eq!(
+        normalize_channel_name("snake_case", ChannelType::Text),
+        "snake_case"
+    );
+    assert_eq!(
+        normalize_channel_name("Café Ünïcode", ChannelType::Text),
+        "café-ünïcode"
+    );
+    assert_eq!(normalize_channel_name("?!#", ChannelType::Text), "");
+}
+
+#[test]
+fn voice_names_keep_their_spelling() {
+    assert_eq!(
+        normalize_channel_name("  Team Room! ", ChannelType::Voice),
+        "Team Room!"
+    );
+    assert_eq!(
+        validate_channel_name("Team Room", ChannelType::Voice, None, &[], &[], None),
+        Ok("Team Room".to_string())
+    );
+
+    // Duplicates are found whatever the case
+    let managed = names(&[(1, "Team Room")]);
+    assert_eq!(
+        validate_channel_name("team room", ChannelType::Voice, None, &managed, &[], None),
+        Err(NameError::Taken { channel_id: 1 })
+    );
+
+    // Naming rules see the name as a text channel would spell it
+    let policy = NamingPolicy {
+        required_prefix: Some("proj-".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        validate_channel_name(
+            "Proj Calls",
+            ChannelType::Voice,
+            Some(&policy),
+            &[],
+            &[],
+            None
+        ),
+        Ok("Proj Calls".to_string())
+    );
+}
+
+#[test]
+fn rejects_empty_and_long_names() {
+    assert_eq!(
+        validate_channel_name("!!!", ChannelType::Text, None, &[], &[], None),
+        Err(NameError::Empty)
+    );
+
+    let long = "a".repeat(MAX_NAME_LENGTH + 1);
+    assert_eq!(
+        validate_channel_name(&long, ChannelType::Text, None, &[], &[], None),
+        Err(NameError::TooLong {
+            length: MAX_NAME_LENGTH + 1
+        })
+    );
+    assert!(validate_channel_name(&long[1..], ChannelType::Text, None, &[], &[], None).is_ok());
+}
+
+#[test]
+fn rejects_names_in_use() {
+    let managed = names(&[(1, "alpha"), (2, "beta")]);
+    let reserved = names(&[(3, "log"), (4, "list")]);
+
+    assert_eq!(
+        validate_channel_name("Alpha", ChannelType::Text, None, &managed, &reserved, None),
+        Err(NameError::Taken { channel_id: 1 })
+    );
+    assert_eq!(
+        validate_channel_name("LOG", ChannelType::Text, None, &managed, &reserved, None),
+        Err(NameError::Reserved { channel_id: 3 })
+    );
+    // A channel may keep its own name, but not take another one's
+    assert_eq!(
+        validate_channel_name(
+            "alpha",
+            ChannelType::Text,
+            None,
+            &managed,
+            &reserved,
+            Some(1)
+        ),
+        Ok("alpha".to_string())
+    );
+    assert_eq!(
+        validate_channel_name(
+            "beta",
+            ChannelType::Text,
+            None,
+            &managed,
+            &reserved,
+            Some(1)
+        ),
+        Err(NameError::Taken { channel_id: 2 })
+    );
+}
+
+#[test]
+fn policy_explains_the_failed_rule() {
+    let policy = NamingPolicy {
+        required_prefix: Some("proj-".to_string()),
+        pattern: Some("^proj-[a-z0-9-]+$".to_string()),
+        reserved_names: vec!["proj-Staff".to_string()],
+        blocked_words: vec!["Admin".to_string(), "official".to_string()],
+    };
+
+    assert_eq!(
+        check_policy(&policy, "alpha"),
+        Err(NameError::MissingPrefix {
+            prefix: "proj-".to_string()
+        })
+    );
+    assert_eq!(
+        check_policy(&policy, "proj-al_pha"),
+        Err(NameError::PatternMismatch {
+            pattern: "^proj-[a-z0-9-]+$".to_string()
+        })
+    );
+    assert_eq!(
+        check_policy(&policy, "proj-staff"),
+        Err(NameError::ReservedName)
+    );
+    assert_eq!(
+        check_policy(&policy, "proj-sysadmins"),
+        Err(NameError::BlockedWord {
+            word: "Admin".to_string()
+        })
+    );
+    assert_eq!(check_policy(&policy, "proj-alpha"), Ok(()));
+
+    // Prefixes are compared the way they would be typed in a name
+    for prefix in ["Proj-", "proj "] {
+        let policy = NamingPolicy {
+            required_prefix: Some(prefix.to_string()),
+            ..NamingPolicy::default()
+        };
+        assert_eq!(check_policy(&policy, "proj-alpha"), Ok(()));
+        assert_eq!(
+            check_policy(&policy, "project"),
+            Err(NameError::MissingPrefix {
+                prefix: "proj-".to_string()
+            })
+        );
+    }
+
+    // Without a policy, for managers, only the usual rules apply
+    assert_eq!(
+        validate_channel_name("Official", ChannelType::Text, None, &[], &[], None),
+        Ok("official".to_string())
+    );
+}
\ No newline at end of file
@@ -0,0 +1,47 @@
+use discord_channel_bot::registration::{CommandChanges, RegisteredCommand};
+
+fn commands(list: &[(&str, u64)]) -> Vec<RegisteredCommand> {
+    list.iter()
+        .map(|(name, version)| RegisteredCommand {
+            name: name.to_string(),
+            version: *version,
+        })
+        .collect()
+}
+
+#[test]
+fn changes_between_registrations() {
+    let before = commands(&[("create", 1), ("rename", 1), ("oldcommand", 1)]);
+    let after = commands(&[("create", 1), ("rename", 2), ("updatelist", 3)]);
+
+    let changes = CommandChanges::between(&before, &after);
+
+    assert_eq!(changes.added, vec!["updatelist"]);
+    assert_eq!(changes.updated, vec!["rename"]);
+    assert_eq!(changes.removed, vec!["oldcommand"]);
+    assert_eq!(changes.unchanged, 1);
+    assert_eq!(
+        changes.summary(),
+        "added /updatelist; updated /rename; removed /oldcommand; 1 unchanged"
+    );
+}
+
+#[test]
+fn identical_registrations_have_no_changes() {
+    let before = commands(&[("create", 1), ("delete", 1)]);
+
+    let changes = CommandChanges::between(&before, &before);
+
+    assert!(changes.is_empty());
+    assert_eq!(changes.summary(), "2 unchanged");
+}
+
+#[test]
+fn clearing_removes_everything() {
+    let before = commands(&[("create", 1), ("delete", 1)]);
+
+    let changes = CommandChanges::between(&before, &[]);
+
+    assert_eq!(changes.removed, vec!["create", "delete"]);
+    assert_eq!(changes.summary(), "removed /create, /delete");
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{dispatch, request_in, temp_path, BotChannels, GUILD_ID, MANAGER};
+use discord_channel_bot::backend::FakeGuild;
+use discord_channel_bot::config::{Config, ListStyle, SharedConfig};
+use discord_channel_bot::registry::ChannelRegistry;
+use serenity::all::GuildId;
+
+struct ReloadTest {
+    guild: FakeGuild,
+    channels: BotChannels,
+    path: String,
+    shared: SharedConfig,
+    registry: ChannelRegistry,
+}
+
+impl ReloadTest {
+    fn new() -> Self {
+        let guild = FakeGuild::new(GUILD_ID);
+        let channels = BotChannels::add_to(&guild);
+        let path = temp_path("toml");
+        let path = path.to_str().unwrap().to_string();
+        std::fs::write(&path, Self::content(&channels, "")).unwrap();
+        let shared = SharedConfig::new(&path, Config::load(&path).unwrap());
+        let registry = ChannelRegistry::load(temp_path("json")).unwrap();
+        Self {
+            guild,
+            channels,
+            path,
+            shared,
+            registry,
+        }
+    }
+
+    fn content(channels: &BotChannels, extra: &str) -> String {
+        format!(
+            "token = \"test\"\n{}",
+            channels.guild_table(GUILD_ID, extra)
+        )
+    }
+
+    async fn reload(&self) {
+        let config = self.shared.current();
+        let request = request_in(
+            &self.guild,
+            "reloadconfig",
+            MANAGER,
+            self.channels.list_channel_id,
+            &[],
+        );
+        dispatch(
+            &self.guild,
+            config.guild(GuildId::new(GUILD_ID)).unwrap(),
+            &self.registry,
+            &self.shared,
+            &request,
+        )
+        .await
+        .unwrap();
+    }
+
+    fn last_log(&self) -> String {
+        let messages = self.guild.channel_messages(self.channels.log_channel_id);
+        messages.last().unwrap().embeds[0].description.clone()
+    }
+}
+
+impl Drop for ReloadTest {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[tokio::test]
+async fn reload_swaps_config_and_logs_diff() {
+    let test = ReloadTest::new();
+    std::fs::write(
+        &test.path,
+        ReloadTest::content(&test.channels, "list_style = \"compact\""),
+    )
+    .unwrap();
+
+    test.reload().await;
+
+    let config = test.shared.current();
+    assert_eq!(
+        config.guild(GuildId::new(GUILD_ID)).unwrap().list_style,
+        ListStyle::Compact
+    );
+    assert_eq!(
+        test.last_log(),
+        format!(
+            "Configuration reloaded by <@{}>\n- list_style: \"per_channel\" -> \"compact\"",
+            MANAGER
+        )
+    );
+}
+
+#[tokio::test]
+async fn invalid_reload_keeps_current_config() {
+    let test = ReloadTest::new();
+    std::fs::write(
+        &test.path,
+        format!(
+            "token = \"test\"\n[guilds.{}]\ncategory_id = 1\nlog_channel_id = 2\nlist_channel_id = 2\nspecial_role_id = 3\nmanager_role_id = 4\n",
+            GUILD_ID
+        ),
+    )
+    .unwrap();
+
+    test.reload().await;
+
+    let config = test.shared.current();
+    assert_eq!(
+        config
+            .guild(GuildId::new(GUILD_ID))
+            .unwrap()
+            .list_channel_id(),
+        test.channels.list_channel_id
+    );
+    assert!(test.last_log().starts_with("Configuration reload by"));
+    assert!(test
+        .guild
+        .responses()
+        .last()
+        .unwrap()
+        .starts_with("Configuration not reloaded"));
+}
+
+#[tokio::test]
+async fn unchanged_reload_bumps_generation() {
+    let test = ReloadTest::new();
+    let before = test.shared.generation();
+
+    test.reload().await;
+
+    assert_eq!(test.shared.generation(), before + 1);
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{TestGuild, CREATOR, OTHER_MEMBER};
+use discord_channel_bot::transcript::{export_transcript, upload_transcript};
+use serenity::all::{ChannelId, ChannelType};
+
+async fn channel_with_history(test: &TestGuild) -> (ChannelId, ChannelId) {
+    let request = test.request(
+        "create",
+        CREATOR,
+        test.list_channel_id,
+        &[("name", "alpha")],
+    );
+    test.run(&request).await.unwrap();
+    let alpha = test.guild.channel_named("alpha").unwrap().id;
+
+    test.guild.add_messages(alpha, CREATOR, 2);
+    test.guild.add_attachment(alpha, OTHER_MEMBER, "notes.pdf");
+    let thread = test
+        .guild
+        .add_channel("questions", ChannelType::PublicThread, Some(alpha));
+    test.guild.add_messages(thread, OTHER_MEMBER, 1);
+    (alpha, thread)
+}
+
+#[tokio::test]
+async fn transcript_covers_messages_threads_and_attachments() {
+    let test = TestGuild::new();
+    let (alpha, thread) = channel_with_history(&test).await;
+
+    let transcript = export_transcript(&test.guild, alpha).await.unwrap();
+
+    assert_eq!(transcript.name, "alpha");
+    assert_eq!(transcript.messages.len(), 3);
+    assert_eq!(transcript.messages[0].author_id, CREATOR);
+    assert_eq!(transcript.messages[2].attachments[0].filename, "notes.pdf");
+    assert_eq!(transcript.threads.len(), 1);
+    assert_eq!(transcript.threads[0].thread_id, thread.get());
+    assert_eq!(transcript.threads[0].messages.len(), 1);
+    assert_eq!(transcript.message_count(), 4);
+    assert_eq!(
+        transcript.summary(),
+        "Transcript of #alpha: 4 message(s), 1 thread(s)"
+    );
+}
+
+#[tokio::test]
+async fn transcript_files_are_json_and_markdown() {
+    let test = TestGuild::new();
+    let (alpha, _) = channel_with_history(&test).await;
+
+    let files = export_transcript(&test.guild, alpha)
+        .await
+        .unwrap()
+        .files()
+        .unwrap();
+
+    let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            format!("transcript-alpha-{}.json", alpha),
+            format!("transcript-alpha-{}.md", alpha)
+        ]
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&files[0].data).unwrap();
+    assert_eq!(json["messages"].as_array().unwrap().len(), 3);
+    assert_eq!(json["threads"][0]["name"], "questions");
+
+    let markdown = String::from_utf8(files[1].data.clone()).unwrap();
+    assert!(markdown.starts_with("# Transcript of #alpha"));
+    assert!(markdown.contains("## Thread: questions"));
+    assert!(markdown.contains("- Attachment: [notes.pdf](https://cdn.example/notes.pdf)"));
+}
+
+#[tokio::test]
+async fn large_transcripts_are_uploaded_in_parts() {
+    let test = TestGuild::new();
+    let (alpha, _) = channel_with_history(&test).await;
+    let transcript = export_transcript(&test.guild, alpha).await.unwrap();
+    let largest = transcript
+        .files()
+        .unwrap()
+        .iter()
+        .map(|f| f.data.len())
+        .max()
+        .unwrap();
+    test.guild.set_upload_limit(largest * 3 / 4);
+
+    upload_transcript(&test.guild, &transcript, test.log_channel_id)
+        .await
+        .unwrap();
+
+    let files = test.guild.files(test.log_channel_id);
+    assert!(files.len() >= 4);
+    assert_eq!(
+        files[0].name,
+        format!("transcript-alpha-{}-part1.json", alpha)
+    );
+    // Every message ends up in exactly one part
+    let messages: usize = files
+        .iter()
+        .filter(|f| f.name.ends_with(".json"))
+        .map(|f| {
+            let json: serde_json::Value = serde_json::from_slice(&f.data).unwrap();
+            let threads = json["threads"].as_array().unwrap();
+            json["messages"].as_array().unwrap().len()
+                + threads
+                    .iter()
+                    .map(|t| t["messages"].as_array().unwrap().len())
+                    .sum::<usize>()
+        })
+        .sum();
+    assert_eq!(messages, 4);
+}
+
+#[tokio::test]
+async fn uploads_fail_when_a_single_message_does_not_fit() {
+    let test = TestGuild::new();
+    let (alpha, _) = channel_with_history(&test).await;
+    let transcript = export_transcript(&test.guild, alpha).await.unwrap();
+    test.guild.set_upload_limit(0);
+
+    let result = upload_transcript(&test.guild, &transcript, test.log_channel_id).await;
+    assert!(result.is_err());
+    assert!(test.guild.files(test.log_channel_id).is_empty());
+}
\ No newline at end of file
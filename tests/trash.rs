@@ -0,0 +1,142 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{TestGuild, CREATOR};
+use discord_channel_bot::backend::Backend;
+use discord_channel_bot::registry::ChannelStatus;
+use discord_channel_bot::trash::{move_to_trash, purge_expired};
+use serenity::all::{ChannelId, UserId};
+
+async fn trashed_channel(test: &TestGuild, name: &str) -> ChannelId {
+    let request = test.request("create", CREATOR, test.list_channel_id, &[("name", name)]);
+    test.run(&request).await.unwrap();
+    let channel_id = test.guild.channel_named(name).unwrap().id;
+    move_to_trash(
+        &test.guild,
+        &test.config,
+        &test.registry,
+        channel_id,
+        UserId::new(CREATOR),
+    )
+    .await
+    .unwrap();
+    channel_id
+}
+
+#[tokio::test]
+async fn only_expired_channels_are_purged() {
+    let mut test = TestGuild::new();
+    test.enable_trash();
+    let alpha = trashed_channel(&test, "alpha").await;
+
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, Utc::now()).await;
+    assert!(purged.is_empty());
+    assert!(test.guild.channel_data(alpha).is_some());
+
+    let later = Utc::now() + Duration::days(7) + Duration::minutes(1);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert_eq!(purged, vec![alpha.get()]);
+    assert!(test.guild.channel_data(alpha).is_none());
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().status,
+        ChannelStatus::Deleted
+    );
+    assert!(test.log_entries().contains(&format!(
+        "[alpha] purged from the trash, deleted by <@{}>",
+        CREATOR
+    )));
+    // Exported when the channel is really deleted
+    assert_eq!(test.guild.files(test.log_channel_id).len(), 2);
+}
+
+#[tokio::test]
+async fn purge_schedule_survives_a_restart() {
+    let mut test = TestGuild::with_config("trash_retention_days = 2");
+    test.enable_trash();
+    let alpha = trashed_channel(&test, "alpha").await;
+
+    let registry = test.reload_registry();
+    let later = Utc::now() + Duration::days(2) + Duration::minutes(1);
+    assert_eq!(
+        registry.due_purges(test.config.guild_id, later).await.len(),
+        1
+    );
+
+    let purged = purge_expired(&test.guild, &test.config, &registry, later).await;
+    assert_eq!(purged, vec![alpha.get()]);
+}
+
+#[tokio::test]
+async fn channels_deleted_by_hand_are_still_cleared() {
+    let mut test = TestGuild::new();
+    test.enable_trash();
+    let alpha = trashed_channel(&test, "alpha").await;
+    test.guild.delete_channel(alpha).await.unwrap();
+
+    let later = Utc::now() + Duration::days(8);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert_eq!(purged, vec![alpha.get()]);
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().status,
+        ChannelStatus::Deleted
+    );
+}
+
+#[tokio::test]
+async fn unreadable_channels_stay_in_the_trash() {
+    let mut test = TestGuild::new();
+    test.enable_trash();
+    let alpha = trashed_channel(&test, "alpha").await;
+    let beta = trashed_channel(&test, "beta").await;
+    test.guild.set_accessible(alpha, false);
+
+    // The other channels of the server are still purged
+    let later = Utc::now() + Duration::days(8);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert_eq!(purged, vec![beta.get()]);
+    assert!(test.guild.channel_data(alpha).is_some());
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().status,
+        ChannelStatus::Trashed
+    );
+    assert!(!test
+        .log_entries()
+        .iter()
+        .any(|e| e.starts_with("[alpha] purged")));
+
+    // Purged on a later run once the channel can be read again
+    test.guild.set_accessible(alpha, true);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert_eq!(purged, vec![alpha.get()]);
+    assert!(test.guild.channel_data(alpha).is_none());
+}
+
+#[tokio::test]
+async fn channels_without_a_transcript_stay_in_the_trash() {
+    let mut test = TestGuild::new();
+    test.enable_trash();
+    let alpha = trashed_channel(&test, "alpha").await;
+    test.guild.set_upload_limit(0);
+
+    let later = Utc::now() + Duration::days(8);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert!(purged.is_empty());
+    assert!(test.guild.channel_data(alpha).is_some());
+    assert_eq!(
+        test.registry.get(alpha.get()).await.unwrap().status,
+        ChannelStatus::Trashed
+    );
+    let failure = "The transcript of [alpha] could not be saved, it stays in the trash until it can: Request entity too large";
+    assert!(test.log_entries().contains(&failure.to_string()));
+
+    // Reported once, not on every run
+    purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    let reports = test.log_entries().iter().filter(|e| *e == failure).count();
+    assert_eq!(reports, 1);
+
+    // Purged once the transcript can be uploaded
+    test.guild.set_upload_limit(usize::MAX);
+    let purged = purge_expired(&test.guild, &test.config, &test.registry, later).await;
+    assert_eq!(purged, vec![alpha.get()]);
+    assert!(test.guild.channel_data(alpha).is_none());
+}
\ No newline at end of file